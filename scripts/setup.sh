@@ -19,6 +19,9 @@ DATA_DIR="$PROJECT_DIR/data"
 # Default mode is cold (for benchmarking)
 MODE="cold"
 
+# Whether to also create the tables used by the `complex` benchmarks
+EXTENDED="false"
+
 # Colors for output
 RED='\033[0;31m'
 GREEN='\033[0;32m'
@@ -49,12 +52,14 @@ show_usage() {
     echo "  -m, --mode MODE    Set PostgreSQL optimization mode:"
     echo "                     cold     - Cold query mode (default, for benchmarking)"
     echo "                     optimized - Optimized mode (for performance testing)"
+    echo "  -e, --extended     Also create the extended schema used by the complex benchmarks"
     echo "  -h, --help        Show this help message"
     echo ""
     echo "Examples:"
     echo "  $0                    # Run with cold query mode (default)"
     echo "  $0 -m cold          # Explicitly set cold query mode"
     echo "  $0 -m optimized     # Run with optimized settings"
+    echo "  $0 --extended       # Include the extended schema (e.g. for cte_prefilter_join)"
 }
 
 parse_args() {
@@ -69,6 +74,10 @@ parse_args() {
                 fi
                 shift 2
                 ;;
+            -e|--extended)
+                EXTENDED="true"
+                shift
+                ;;
             -h|--help)
                 show_usage
                 exit 0
@@ -174,6 +183,14 @@ setup_container() {
         log_success "Database setup completed"
     fi
 
+    # Optionally add the extended schema on top of the base tables
+    if [[ "$EXTENDED" == "true" ]]; then
+        local extended_script="$SCRIPT_DIR/setup_extended_schema.sql"
+        log_info "Running extended schema script..."
+        docker exec -i "$CONTAINER_NAME" psql -U "$POSTGRES_USER" -d "$POSTGRES_DB" < "$extended_script"
+        log_success "Extended schema setup completed"
+    fi
+
     # Apply mode-specific configuration
     if [[ "$MODE" == "cold" ]]; then
         local cold_script="$SCRIPT_DIR/cold_query_init.sql"
@@ -0,0 +1,21 @@
+-- Extended schema for the multi-step (`complex`) benchmarks
+-- Run after setup_test_db.sql; `./scripts/setup.sh --extended` does this for you.
+
+-- One details row per override, keyed by the override's primary key, so joining
+-- the matched overrides to their details keeps at most one row per looked-up hash
+DROP TABLE IF EXISTS override_details;
+CREATE TABLE override_details (
+    override_id UUID PRIMARY KEY REFERENCES overrides (id),
+    category TEXT NOT NULL,
+    priority INTEGER NOT NULL
+);
+
+INSERT INTO override_details (override_id, category, priority)
+SELECT
+    id,
+    -- A handful of low-cardinality categories, as in a typical lookup table
+    (ARRAY['alpha', 'beta', 'gamma', 'delta'])[1 + (get_byte(hash, 0) % 4)],
+    get_byte(hash, 1)
+FROM overrides;
+
+VACUUM ANALYZE override_details;
@@ -47,6 +47,54 @@ END $$;
 -- Analyze table for better query planning
 VACUUM ANALYZE overrides;
 
+-- Key-width comparison tables: identical shape apart from the key column type.
+-- The BIGINT key is the first 8 bytes of the hash, so both tables hold the same
+-- logical rows and a hash maps to the same response in either table.
+DROP TABLE IF EXISTS overrides_bytea_key;
+CREATE TABLE overrides_bytea_key (
+    key BYTEA PRIMARY KEY,
+    response TEXT NOT NULL
+);
+INSERT INTO overrides_bytea_key (key, response)
+SELECT hash, response FROM overrides;
+
+DROP TABLE IF EXISTS overrides_bigint_key;
+CREATE TABLE overrides_bigint_key (
+    key BIGINT PRIMARY KEY,
+    response TEXT NOT NULL
+);
+INSERT INTO overrides_bigint_key (key, response)
+SELECT ('x' || encode(substring(hash FROM 1 FOR 8), 'hex'))::bit(64)::bigint, response
+FROM overrides;
+
+-- UUID key: the first 16 bytes of the hash
+DROP TABLE IF EXISTS overrides_uuid_key;
+CREATE TABLE overrides_uuid_key (
+    key UUID PRIMARY KEY,
+    response TEXT NOT NULL
+);
+INSERT INTO overrides_uuid_key (key, response)
+SELECT encode(substring(hash FROM 1 FOR 16), 'hex')::uuid, response
+FROM overrides;
+
+-- TIMESTAMP key: the first 52 bits of the hash as microseconds after 2000-01-01
+-- (52 bits stay exact through the interval multiplication's double arithmetic)
+DROP TABLE IF EXISTS overrides_timestamp_key;
+CREATE TABLE overrides_timestamp_key (
+    key TIMESTAMP PRIMARY KEY,
+    response TEXT NOT NULL
+);
+INSERT INTO overrides_timestamp_key (key, response)
+SELECT timestamp '2000-01-01'
+    + ('x' || substring(encode(hash, 'hex') FROM 1 FOR 13))::bit(52)::bigint * interval '1 microsecond',
+    response
+FROM overrides;
+
+VACUUM ANALYZE overrides_bytea_key;
+VACUUM ANALYZE overrides_bigint_key;
+VACUUM ANALYZE overrides_uuid_key;
+VACUUM ANALYZE overrides_timestamp_key;
+
 -- Display table statistics
 SELECT
     schemaname,
@@ -0,0 +1,159 @@
+//! Setup cost amortization across ID-set sizes
+//!
+//! Strategies with fixed setup (creating a temp table, preparing statements) pay it
+//! once per lookup, so how they rank depends on the ID-set size. Given runs of the
+//! same benchmark at several sizes (`--sweep`), a least-squares line
+//! `median = fixed_cost + per_id_cost * n` separates the fixed overhead from the
+//! marginal cost per ID. Where two lines cross is the size at which the strategy
+//! with the higher fixed cost starts to win.
+
+use crate::BenchmarkStats;
+use std::collections::BTreeMap;
+
+/// Linear cost model fitted to one benchmark's medians
+#[derive(Debug, Clone)]
+pub struct CostFit {
+    pub name: String,
+    /// Estimated fixed cost per lookup, in nanoseconds (can be slightly negative from noise)
+    pub fixed_cost_ns: f64,
+    /// Estimated marginal cost per ID, in nanoseconds
+    pub per_id_cost_ns: f64,
+    /// Coefficient of determination of the fit; trivially 1.0 with two sizes
+    pub r_squared: f64,
+    /// Number of distinct ID-set sizes fitted
+    pub sizes: usize,
+}
+
+impl CostFit {
+    /// ID-set size above which this strategy is faster than `other`
+    ///
+    /// # Returns
+    /// * `Option<f64>` - Size where the lines cross, or `None` if this strategy never
+    ///   overtakes `other` at a positive size
+    pub fn break_even(&self, other: &CostFit) -> Option<f64> {
+        let per_id_saving = other.per_id_cost_ns - self.per_id_cost_ns;
+        if per_id_saving <= 0.0 {
+            return None;
+        }
+        let n = (self.fixed_cost_ns - other.fixed_cost_ns) / per_id_saving;
+        (n > 0.0).then_some(n)
+    }
+}
+
+/// Fit `median = fixed + per_id * n` for every benchmark run at two or more sizes
+///
+/// Each size contributes one point, the benchmark's median at that size, so
+/// iteration counts don't weight the fit.
+///
+/// # Arguments
+/// * `results` - Results of a sweep; entries are grouped by name and keyed by `input_size`
+///
+/// # Returns
+/// * `Vec<CostFit>` - One fit per benchmark with at least two distinct sizes, sorted by name
+pub fn fit_costs(results: &[BenchmarkStats]) -> Vec<CostFit> {
+    let mut points: BTreeMap<&str, BTreeMap<usize, f64>> = BTreeMap::new();
+    for stats in results.iter().filter(|s| !s.runs.is_empty()) {
+        points
+            .entry(stats.name.as_str())
+            .or_default()
+            .insert(stats.input_size, stats.median().as_nanos() as f64);
+    }
+
+    points
+        .into_iter()
+        .filter(|(_, by_size)| by_size.len() >= 2)
+        .map(|(name, by_size)| {
+            let (fixed_cost_ns, per_id_cost_ns, r_squared) = least_squares(&by_size);
+            CostFit {
+                name: name.to_string(),
+                fixed_cost_ns,
+                per_id_cost_ns,
+                r_squared,
+                sizes: by_size.len(),
+            }
+        })
+        .collect()
+}
+
+/// Ordinary least squares over `(size, median_ns)` points
+///
+/// # Returns
+/// * `(f64, f64, f64)` - Intercept, slope and R²
+fn least_squares(points: &BTreeMap<usize, f64>) -> (f64, f64, f64) {
+    let n = points.len() as f64;
+    let mean_x = points.keys().map(|&x| x as f64).sum::<f64>() / n;
+    let mean_y = points.values().sum::<f64>() / n;
+
+    let mut sxx = 0.0;
+    let mut sxy = 0.0;
+    let mut syy = 0.0;
+    for (&x, &y) in points {
+        let dx = x as f64 - mean_x;
+        let dy = y - mean_y;
+        sxx += dx * dx;
+        sxy += dx * dy;
+        syy += dy * dy;
+    }
+
+    let slope = sxy / sxx;
+    let intercept = mean_y - slope * mean_x;
+    // All medians equal: the flat line explains them perfectly
+    let r_squared = if syy == 0.0 {
+        1.0
+    } else {
+        (sxy * sxy) / (sxx * syy)
+    };
+    (intercept, slope, r_squared)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn at_size(name: &str, input_size: usize, median_ns: u64) -> BenchmarkStats {
+        let mut stats = BenchmarkStats::new(name.to_string(), String::new(), input_size);
+        stats.runs = vec![Duration::from_nanos(median_ns)];
+        stats
+    }
+
+    #[test]
+    fn exact_linear_medians_recover_intercept_and_slope() {
+        // 5,000ns fixed plus 20ns per ID
+        let results: Vec<BenchmarkStats> = [100, 1_000, 10_000]
+            .into_iter()
+            .map(|n| at_size("temp_table_join", n, 5_000 + 20 * n as u64))
+            .collect();
+
+        let fits = fit_costs(&results);
+        assert_eq!(fits.len(), 1);
+        let fit = &fits[0];
+        assert_eq!(fit.name, "temp_table_join");
+        assert_eq!(fit.sizes, 3);
+        assert!((fit.fixed_cost_ns - 5_000.0).abs() < 1e-6, "{:?}", fit);
+        assert!((fit.per_id_cost_ns - 20.0).abs() < 1e-9, "{:?}", fit);
+        assert!((fit.r_squared - 1.0).abs() < 1e-12, "{:?}", fit);
+    }
+
+    #[test]
+    fn equal_medians_fit_a_flat_line_with_r_squared_one() {
+        let points = BTreeMap::from([(100, 7_000.0), (1_000, 7_000.0), (10_000, 7_000.0)]);
+        assert_eq!(least_squares(&points), (7_000.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn benchmarks_run_at_a_single_size_are_not_fitted() {
+        let results = [
+            at_size("any_array", 1_000, 10_000),
+            // A second run at the same size is still one point
+            at_size("any_array", 1_000, 12_000),
+            at_size("in_list", 100, 2_000),
+            at_size("in_list", 1_000, 11_000),
+        ];
+
+        let fits = fit_costs(&results);
+        let names: Vec<&str> = fits.iter().map(|fit| fit.name.as_str()).collect();
+        assert_eq!(names, ["in_list"]);
+        assert_eq!(fits[0].sizes, 2);
+    }
+}
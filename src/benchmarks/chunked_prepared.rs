@@ -1,12 +1,46 @@
-use crate::{BenchmarkContext, BenchmarkError, BenchmarkResult, BenchmarkTest, ExampleData};
+use crate::explain::{ExplainOptions, collect_plan};
+use crate::{
+    BenchmarkContext, BenchmarkError, BenchmarkIds, BenchmarkResult, BenchmarkTest, ExampleData,
+};
 use async_trait::async_trait;
+use std::borrow::Cow;
 use tracing::instrument;
 
-/// Maximum number of values per prepared statement
-const MAX_VALUES: usize = 2000;
+/// Default maximum number of values per prepared statement
+pub(super) const MAX_VALUES: usize = 2000;
 
 /// Benchmark that uses chunked prepared statements
-pub struct ChunkedPreparedBenchmark;
+///
+/// Each chunk runs as its own auto-commit query, possibly on a different pooled
+/// connection. See `chunked_prepared_txn` for the single-transaction variant.
+/// The default chunk size is named `chunked_prepared`, other sizes
+/// `chunked_prepared_<size>`.
+pub struct ChunkedPreparedBenchmark {
+    /// Maximum number of values per prepared statement
+    pub(super) max_values: usize,
+    name: Cow<'static, str>,
+}
+
+impl ChunkedPreparedBenchmark {
+    /// Create a benchmark binding at most `max_values` IDs per statement
+    ///
+    /// # Arguments
+    /// * `max_values` - Chunk size; 0 is treated as 1
+    pub fn with_chunk_size(max_values: usize) -> Self {
+        let max_values = max_values.max(1);
+        let name = match max_values {
+            MAX_VALUES => Cow::Borrowed("chunked_prepared"),
+            size => Cow::Owned(format!("chunked_prepared_{}", size)),
+        };
+        Self { max_values, name }
+    }
+}
+
+impl Default for ChunkedPreparedBenchmark {
+    fn default() -> Self {
+        Self::with_chunk_size(MAX_VALUES)
+    }
+}
 
 #[async_trait]
 impl BenchmarkTest for ChunkedPreparedBenchmark {
@@ -14,15 +48,20 @@ impl BenchmarkTest for ChunkedPreparedBenchmark {
     async fn run(
         &self,
         context: &BenchmarkContext,
-        ids: &[[u8; 32]],
+        ids: BenchmarkIds<'_>,
     ) -> BenchmarkResult<Vec<ExampleData>> {
-        let query = build_prepared_query(MAX_VALUES);
+        let ids = ids.bytes()?;
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let query = build_prepared_query(context, self.max_values);
         let mut all_overrides = Vec::new();
 
-        for chunk in ids.chunks(MAX_VALUES) {
+        for chunk in ids.chunks(self.max_values) {
             // Build the query with the appropriate number of placeholders
-            let chunk_query = if chunk.len() < MAX_VALUES {
-                build_prepared_query(chunk.len())
+            let chunk_query = if chunk.len() < self.max_values {
+                build_prepared_query(context, chunk.len())
             } else {
                 query.clone()
             };
@@ -34,10 +73,12 @@ impl BenchmarkTest for ChunkedPreparedBenchmark {
             }
 
             // Execute the query
+            let mut conn = context.connection().await?;
             let overrides: Vec<ExampleData> = query_builder
-                .fetch_all(&context.pool)
+                .fetch_all(&mut *conn)
                 .await
                 .map_err(BenchmarkError::Database)?;
+            conn.finish().await?;
 
             all_overrides.extend(overrides);
         }
@@ -45,26 +86,70 @@ impl BenchmarkTest for ChunkedPreparedBenchmark {
         Ok(all_overrides)
     }
 
-    fn name(&self) -> &'static str {
-        "chunked_prepared"
+    async fn explain(
+        &self,
+        context: &BenchmarkContext,
+        ids: BenchmarkIds<'_>,
+        options: &ExplainOptions,
+    ) -> BenchmarkResult<Option<String>> {
+        let ids = ids.bytes()?;
+        // Every chunk shares the same shape, so the first one is representative
+        let Some(chunk) = ids.chunks(self.max_values).next() else {
+            return Ok(None);
+        };
+
+        let chunk_query = options.wrap(&build_prepared_query(context, chunk.len()));
+        let mut query_builder = sqlx::query(&chunk_query);
+        for id in chunk.iter() {
+            query_builder = query_builder.bind(*id);
+        }
+
+        let mut conn = context.connection().await?;
+        let rows = query_builder.fetch_all(&mut *conn).await?;
+        conn.finish().await?;
+
+        Ok(Some(collect_plan(&rows)?))
+    }
+
+    fn rendered_query(&self, context: &BenchmarkContext, num_ids: usize) -> Option<String> {
+        // The first chunk's; later chunks share its shape
+        Some(build_prepared_query(context, num_ids.min(self.max_values)))
+    }
+
+    fn name(&self) -> &str {
+        &self.name
     }
 
     fn description(&self) -> &'static str {
         "Splits IDs into chunks and uses prepared statements with placeholders"
     }
+
+    fn supports_query_template(&self) -> bool {
+        true
+    }
+
+    fn session_requirement(&self) -> Option<&'static str> {
+        Some(
+            "reuses the per-chunk prepared statement across auto-commit queries, \
+             which a transaction pooler may route to a different server connection",
+        )
+    }
 }
 
 /// Build a prepared query string with the specified number of placeholders
-fn build_prepared_query(num_values: usize) -> String {
-    let mut query = "SELECT response FROM overrides WHERE hash IN (".to_owned();
+pub(super) fn build_prepared_query(context: &BenchmarkContext, num_values: usize) -> String {
+    let mut placeholders = "(".to_owned();
 
     for i in 0..num_values {
         if i > 0 {
-            query.push_str(", ");
+            placeholders.push_str(", ");
         }
-        query.push_str(&format!("${}", i + 1));
+        placeholders.push_str(&format!("${}", i + 1));
     }
 
-    query.push_str(");");
-    query
+    placeholders.push(')');
+    match &context.query_template {
+        Some(template) => template.render(&placeholders),
+        None => context.schema.lookup_query(&format!("IN {}", placeholders)),
+    }
 }
@@ -0,0 +1,99 @@
+use super::ChunkedPreparedBenchmark;
+use super::chunked_prepared::{MAX_VALUES, build_prepared_query};
+use crate::explain::ExplainOptions;
+use crate::{
+    BenchmarkContext, BenchmarkError, BenchmarkIds, BenchmarkResult, BenchmarkTest, ExampleData,
+};
+use async_trait::async_trait;
+use tracing::instrument;
+
+/// Benchmark that runs every chunked prepared statement inside one transaction
+///
+/// Identical to `chunked_prepared` apart from the transaction boundary, so the
+/// difference between the two is the cost of a commit and a pool checkout per chunk.
+///
+/// Semantics differ on failure: here a failing chunk aborts the transaction and the
+/// whole lookup is rolled back, while in `chunked_prepared` the chunks before it
+/// have already committed independently. Both return the error rather than partial
+/// results. Under the default READ COMMITTED isolation every chunk still takes its
+/// own snapshot, so a single transaction doesn't make the chunks mutually consistent.
+pub struct ChunkedPreparedTxnBenchmark;
+
+#[async_trait]
+impl BenchmarkTest for ChunkedPreparedTxnBenchmark {
+    #[instrument(skip(self, context, ids), fields(ids_count = ids.len()))]
+    async fn run(
+        &self,
+        context: &BenchmarkContext,
+        ids: BenchmarkIds<'_>,
+    ) -> BenchmarkResult<Vec<ExampleData>> {
+        let ids = ids.bytes()?;
+        // Nothing to look up, so don't open a transaction for zero chunks
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let query = build_prepared_query(context, MAX_VALUES);
+        let mut all_overrides = Vec::new();
+
+        // All chunks share one connection and one transaction
+        let mut transaction = context.begin().await?;
+        context.apply_local_settings(&mut transaction).await?;
+
+        for chunk in ids.chunks(MAX_VALUES) {
+            // Build the query with the appropriate number of placeholders
+            let chunk_query = if chunk.len() < MAX_VALUES {
+                build_prepared_query(context, chunk.len())
+            } else {
+                query.clone()
+            };
+
+            // Bind the IDs to the query
+            let mut query_builder = sqlx::query_as(&chunk_query);
+            for id in chunk.iter() {
+                query_builder = query_builder.bind(*id);
+            }
+
+            // Execute the query; on error the transaction is dropped and rolled back
+            let overrides: Vec<ExampleData> = query_builder
+                .fetch_all(&mut *transaction)
+                .await
+                .map_err(BenchmarkError::Database)?;
+
+            all_overrides.extend(overrides);
+        }
+
+        transaction.commit().await?;
+
+        Ok(all_overrides)
+    }
+
+    async fn explain(
+        &self,
+        context: &BenchmarkContext,
+        ids: BenchmarkIds<'_>,
+        options: &ExplainOptions,
+    ) -> BenchmarkResult<Option<String>> {
+        // The per-chunk statement is the same as in the auto-commit variant
+        ChunkedPreparedBenchmark::default()
+            .explain(context, ids, options)
+            .await
+    }
+
+    fn rendered_query(&self, context: &BenchmarkContext, num_ids: usize) -> Option<String> {
+        // The first chunk's; later chunks share its shape
+        Some(build_prepared_query(context, num_ids.min(MAX_VALUES)))
+    }
+
+    fn name(&self) -> &'static str {
+        "chunked_prepared_txn"
+    }
+
+    fn description(&self) -> &'static str {
+        "Chunked prepared statements executed inside a single transaction"
+    }
+
+    fn supports_query_template(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,105 @@
+use crate::explain::{ExplainOptions, collect_plan};
+use crate::{
+    BenchmarkContext, BenchmarkError, BenchmarkIds, BenchmarkResult, BenchmarkTest, ExampleData,
+    UNDEFINED_TABLE,
+};
+use async_trait::async_trait;
+use tracing::instrument;
+
+/// Query executed by this benchmark
+///
+/// `MATERIALIZED` stops the planner from inlining the CTE, so the membership test
+/// runs first and only the matched rows reach the join.
+const QUERY: &str = "WITH matches AS MATERIALIZED ( \
+        SELECT id, response FROM overrides WHERE hash = ANY($1) \
+    ) \
+    SELECT matches.response || ':' || details.category AS response \
+    FROM matches \
+    JOIN override_details AS details ON details.override_id = matches.id;";
+
+/// Benchmark of a two-step query: pre-filter into a materialized CTE, then join
+///
+/// Models a lookup that enriches the matched rows from a second table, rather than
+/// a bare membership test. Requires `override_details` from
+/// `scripts/setup_extended_schema.sql`.
+pub struct CtePrefilterJoinBenchmark;
+
+impl CtePrefilterJoinBenchmark {
+    /// Point at the extended schema script if the joined table doesn't exist
+    fn map_missing_table(error: sqlx::Error) -> BenchmarkError {
+        match &error {
+            sqlx::Error::Database(db) if db.code().as_deref() == Some(UNDEFINED_TABLE) => {
+                BenchmarkError::Setup {
+                    message: format!(
+                        "{}; run scripts/setup_extended_schema.sql (or scripts/setup.sh --extended)",
+                        db.message()
+                    ),
+                }
+            }
+            _ => BenchmarkError::Database(error),
+        }
+    }
+}
+
+#[async_trait]
+impl BenchmarkTest for CtePrefilterJoinBenchmark {
+    #[instrument(skip(self, context, ids), fields(ids_count = ids.len()))]
+    async fn run(
+        &self,
+        context: &BenchmarkContext,
+        ids: BenchmarkIds<'_>,
+    ) -> BenchmarkResult<Vec<ExampleData>> {
+        let ids = ids.bytes()?;
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut conn = context.connection().await?;
+        let result: Vec<ExampleData> = sqlx::query_as(QUERY)
+            .bind(ids)
+            .fetch_all(&mut *conn)
+            .await
+            .map_err(Self::map_missing_table)?;
+        conn.finish().await?;
+
+        Ok(result)
+    }
+
+    async fn explain(
+        &self,
+        context: &BenchmarkContext,
+        ids: BenchmarkIds<'_>,
+        options: &ExplainOptions,
+    ) -> BenchmarkResult<Option<String>> {
+        let ids = ids.bytes()?;
+        let mut conn = context.connection().await?;
+        let rows = sqlx::query(&options.wrap(QUERY))
+            .bind(ids)
+            .fetch_all(&mut *conn)
+            .await
+            .map_err(Self::map_missing_table)?;
+        conn.finish().await?;
+
+        Ok(Some(collect_plan(&rows)?))
+    }
+
+    fn rendered_query(&self, _context: &BenchmarkContext, _num_ids: usize) -> Option<String> {
+        Some(QUERY.to_string())
+    }
+
+    fn name(&self) -> &'static str {
+        "cte_prefilter_join"
+    }
+
+    fn description(&self) -> &'static str {
+        "Pre-filters with ANY($1) in a materialized CTE, then joins a details table"
+    }
+
+    fn returns_responses(&self) -> bool {
+        false
+    }
+
+    fn tags(&self) -> &'static [&'static str] {
+        &["complex"]
+    }
+}
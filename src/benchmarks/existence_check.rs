@@ -0,0 +1,88 @@
+use crate::explain::{ExplainOptions, collect_plan};
+use crate::{
+    BenchmarkContext, BenchmarkError, BenchmarkIds, BenchmarkResult, BenchmarkTest, ExampleData,
+    utils,
+};
+use async_trait::async_trait;
+use tracing::instrument;
+
+/// Condition on the key column matched by this benchmark
+const PREDICATE: &str = "= ANY($1)";
+
+/// Benchmark that only asks which of the IDs exist
+///
+/// Models filtering a batch of IDs down to the known ones, as in dedup pipelines:
+/// only the matching 32-byte keys come back, not the responses. Each key is returned
+/// hex-encoded in [`ExampleData::response`].
+pub struct ExistenceCheckBenchmark;
+
+#[async_trait]
+impl BenchmarkTest for ExistenceCheckBenchmark {
+    #[instrument(skip(self, context, ids), fields(ids_count = ids.len()))]
+    async fn run(
+        &self,
+        context: &BenchmarkContext,
+        ids: BenchmarkIds<'_>,
+    ) -> BenchmarkResult<Vec<ExampleData>> {
+        let ids = ids.bytes()?;
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut conn = context.connection().await?;
+        let keys: Vec<Vec<u8>> = sqlx::query_scalar(&context.schema.key_query(PREDICATE))
+            .bind(ids)
+            .fetch_all(&mut *conn)
+            .await
+            .map_err(BenchmarkError::Database)?;
+        conn.finish().await?;
+
+        Ok(keys
+            .into_iter()
+            .map(|key| ExampleData {
+                response: hex::encode(&key),
+                hash: Some(key),
+            })
+            .collect())
+    }
+
+    async fn explain(
+        &self,
+        context: &BenchmarkContext,
+        ids: BenchmarkIds<'_>,
+        options: &ExplainOptions,
+    ) -> BenchmarkResult<Option<String>> {
+        let ids = ids.bytes()?;
+        let mut conn = context.connection().await?;
+        let rows = sqlx::query(&options.wrap(&context.schema.key_query(PREDICATE)))
+            .bind(ids)
+            .fetch_all(&mut *conn)
+            .await?;
+        conn.finish().await?;
+
+        Ok(Some(collect_plan(&rows)?))
+    }
+
+    fn rendered_query(&self, context: &BenchmarkContext, _num_ids: usize) -> Option<String> {
+        Some(context.schema.key_query(PREDICATE))
+    }
+
+    fn name(&self) -> &'static str {
+        "existence_check"
+    }
+
+    fn description(&self) -> &'static str {
+        "Returns only the matching keys (ANY array), for filtering IDs to the known ones"
+    }
+
+    fn returns_responses(&self) -> bool {
+        false
+    }
+
+    fn validate(&self, ids: BenchmarkIds<'_>, results: &[ExampleData]) -> BenchmarkResult<()> {
+        let ids = ids.bytes()?;
+        utils::validate_results(results, ids.len())?;
+        // Every returned key must be one of the requested IDs, each at most once
+        utils::validate_results_match(results, &ids.iter().copied().collect())
+    }
+}
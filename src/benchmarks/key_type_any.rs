@@ -0,0 +1,103 @@
+use crate::explain::{ExplainOptions, collect_plan};
+use crate::keys::{KeyType, Keys};
+use crate::{BenchmarkContext, BenchmarkIds, BenchmarkResult, BenchmarkTest, ExampleData};
+use async_trait::async_trait;
+
+/// Query executed for a key type
+fn query(key_type: KeyType) -> &'static str {
+    match key_type {
+        KeyType::Bytea => "SELECT response FROM overrides_bytea_key WHERE key = ANY($1);",
+        KeyType::Bigint => "SELECT response FROM overrides_bigint_key WHERE key = ANY($1);",
+        KeyType::Uuid => "SELECT response FROM overrides_uuid_key WHERE key = ANY($1);",
+        KeyType::Timestamp => "SELECT response FROM overrides_timestamp_key WHERE key = ANY($1);",
+    }
+}
+
+/// Benchmark comparing `= ANY($1)` across key column types
+///
+/// Every variant looks up the same logical IDs, isolating the wire-encoding and
+/// comparison cost of the key's width and type. Deriving the non-BYTEA keys costs a
+/// few nanoseconds per ID; a real schema would already hold them, so this slightly
+/// favours BYTEA.
+pub struct KeyTypeAnyBenchmark {
+    key_type: KeyType,
+}
+
+impl KeyTypeAnyBenchmark {
+    /// Create a benchmark for the given key type
+    pub fn new(key_type: KeyType) -> Self {
+        Self { key_type }
+    }
+}
+
+#[async_trait]
+impl BenchmarkTest for KeyTypeAnyBenchmark {
+    async fn run(
+        &self,
+        context: &BenchmarkContext,
+        ids: BenchmarkIds<'_>,
+    ) -> BenchmarkResult<Vec<ExampleData>> {
+        let ids = ids.bytes()?;
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let keys = Keys::from_ids(self.key_type, ids);
+        let mut conn = context.connection().await?;
+        let result: Vec<ExampleData> = keys
+            .bind_query_as(sqlx::query_as(query(self.key_type)))
+            .fetch_all(&mut *conn)
+            .await?;
+        conn.finish().await?;
+
+        Ok(result)
+    }
+
+    async fn explain(
+        &self,
+        context: &BenchmarkContext,
+        ids: BenchmarkIds<'_>,
+        options: &ExplainOptions,
+    ) -> BenchmarkResult<Option<String>> {
+        let ids = ids.bytes()?;
+        let query = options.wrap(query(self.key_type));
+        let mut conn = context.connection().await?;
+        let rows = Keys::from_ids(self.key_type, ids)
+            .bind_query(sqlx::query(&query))
+            .fetch_all(&mut *conn)
+            .await?;
+        conn.finish().await?;
+
+        Ok(Some(collect_plan(&rows)?))
+    }
+
+    fn rendered_query(&self, _context: &BenchmarkContext, _num_ids: usize) -> Option<String> {
+        Some(query(self.key_type).to_string())
+    }
+
+    fn name(&self) -> &'static str {
+        match self.key_type {
+            KeyType::Bytea => "key_type_any_bytea",
+            KeyType::Bigint => "key_type_any_bigint",
+            KeyType::Uuid => "key_type_any_uuid",
+            KeyType::Timestamp => "key_type_any_timestamp",
+        }
+    }
+
+    fn description(&self) -> &'static str {
+        match self.key_type {
+            KeyType::Bytea => "ANY($1) over a BYTEA primary key with 32-byte hashes",
+            KeyType::Bigint => "ANY($1) over a BIGINT primary key with the same logical IDs",
+            KeyType::Uuid => "ANY($1) over a UUID primary key with the same logical IDs",
+            KeyType::Timestamp => "ANY($1) over a TIMESTAMP primary key with the same logical IDs",
+        }
+    }
+
+    fn tags(&self) -> &'static [&'static str] {
+        &["keytype"]
+    }
+
+    fn key_type(&self) -> KeyType {
+        self.key_type
+    }
+}
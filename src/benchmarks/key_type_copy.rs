@@ -0,0 +1,140 @@
+use crate::copy::BinaryCopyWriter;
+use crate::keys::{KeyType, Keys};
+use crate::{BenchmarkContext, BenchmarkIds, BenchmarkResult, BenchmarkTest, ExampleData};
+use async_trait::async_trait;
+use tracing::instrument;
+
+/// Benchmark that loads keys of a given type into a temp table with binary COPY
+///
+/// The key-type counterpart of `temp_table_binary_copy`: the temp table's column has
+/// the same type as the looked-up table's key, and each key is sent in that type's
+/// binary wire format, so the comparison covers the COPY encoding as well as the
+/// index lookups.
+pub struct KeyTypeCopyBenchmark {
+    key_type: KeyType,
+}
+
+impl KeyTypeCopyBenchmark {
+    /// Create a benchmark for the given key type
+    pub fn new(key_type: KeyType) -> Self {
+        Self { key_type }
+    }
+}
+
+#[async_trait]
+impl BenchmarkTest for KeyTypeCopyBenchmark {
+    #[instrument(skip(self, context, ids), fields(ids_count = ids.len()))]
+    async fn run(
+        &self,
+        context: &BenchmarkContext,
+        ids: BenchmarkIds<'_>,
+    ) -> BenchmarkResult<Vec<ExampleData>> {
+        let ids = ids.bytes()?;
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let keys = Keys::from_ids(self.key_type, ids);
+        let table = context.unique_temp_name("temp_keys");
+        let mut transaction = context.begin().await?;
+
+        // Create a temporary unlogged table with the same key type as the lookup table
+        sqlx::query(&format!(
+            "CREATE UNLOGGED TABLE {} (key {} PRIMARY KEY);",
+            table,
+            self.key_type.sql_type()
+        ))
+        .execute(&mut *transaction)
+        .await?;
+
+        // Get a copy-in handle for the temporary table with binary format
+        let mut handle = transaction
+            .copy_in_raw(&format!(
+                "COPY {} (key) FROM STDIN WITH (FORMAT BINARY)",
+                table
+            ))
+            .await?;
+
+        // Encode each key in its type's wire format
+        let mut writer = BinaryCopyWriter::with_capacity(
+            1,
+            keys.len(),
+            BinaryCopyWriter::tuple_size(&[self.key_type.encoded_len()]),
+        );
+        keys.write_copy(&mut writer);
+
+        // Send all data in one operation
+        handle.send(writer.finish()).await?;
+        handle.finish().await?;
+
+        // Apply any SET LOCAL overrides (e.g. planner toggles) before the query
+        context.apply_local_settings(&mut transaction).await?;
+
+        // Perform the query using the temporary table
+        let result: Vec<ExampleData> = sqlx::query_as(&format!(
+            "SELECT response FROM {} WHERE key IN (SELECT key FROM {});",
+            self.key_type.table(),
+            table
+        ))
+        .fetch_all(&mut *transaction)
+        .await?;
+
+        // Rollback to clean up the temporary table
+        transaction.rollback().await?;
+
+        Ok(result)
+    }
+
+    fn uses_temp_table(&self) -> bool {
+        true
+    }
+
+    fn rendered_query(&self, _context: &BenchmarkContext, _num_ids: usize) -> Option<String> {
+        Some(format!(
+            "SELECT response FROM {} WHERE key IN (SELECT key FROM temp_keys_<random>);",
+            self.key_type.table()
+        ))
+    }
+
+    fn name(&self) -> &'static str {
+        match self.key_type {
+            KeyType::Bytea => "key_type_copy_bytea",
+            KeyType::Bigint => "key_type_copy_bigint",
+            KeyType::Uuid => "key_type_copy_uuid",
+            KeyType::Timestamp => "key_type_copy_timestamp",
+        }
+    }
+
+    fn description(&self) -> &'static str {
+        match self.key_type {
+            KeyType::Bytea => {
+                "Binary COPY of 32-byte BYTEA keys into a temp table, then IN over a BYTEA key"
+            }
+            KeyType::Bigint => {
+                "Binary COPY of BIGINT keys into a temp table, then IN over a BIGINT key"
+            }
+            KeyType::Uuid => "Binary COPY of UUID keys into a temp table, then IN over a UUID key",
+            KeyType::Timestamp => {
+                "Binary COPY of TIMESTAMP keys into a temp table, then IN over a TIMESTAMP key"
+            }
+        }
+    }
+
+    fn tags(&self) -> &'static [&'static str] {
+        &["keytype"]
+    }
+
+    fn key_type(&self) -> KeyType {
+        self.key_type
+    }
+
+    async fn cleanup(&self, context: &BenchmarkContext) -> BenchmarkResult<()> {
+        // Clear caches after the benchmark
+        context.clear_caches().await?;
+
+        // Clean up any leftover temp tables (just in case)
+        let _ = context.drop_temp_tables().await;
+
+        Ok(())
+    }
+}
@@ -1,42 +1,127 @@
 mod any_array;
 mod chunked_prepared;
+mod chunked_prepared_txn;
+mod cte_prefilter_join;
+mod existence_check;
+mod key_type_any;
+mod key_type_copy;
+mod parallel_any_scatter;
+mod prepared_cached;
 mod raw_sql_large_in;
 mod temp_table_any;
 mod temp_table_binary_copy;
+mod temp_table_binary_freeze;
 mod temp_table_binary_no_index;
+mod temp_table_composite_copy;
 mod temp_table_join;
 mod temp_table_optimized_binary;
+mod temp_table_optimized_freeze;
+mod temp_table_storage;
 mod temp_table_text_copy;
+mod temp_table_uuid_join;
 mod unnest_array;
+mod values_join;
 
 // Re-export all benchmark implementations
 pub use any_array::AnyArrayBenchmark;
 pub use chunked_prepared::ChunkedPreparedBenchmark;
-pub use raw_sql_large_in::RawSqlLargeInBenchmark;
+pub use chunked_prepared_txn::ChunkedPreparedTxnBenchmark;
+pub use cte_prefilter_join::CtePrefilterJoinBenchmark;
+pub use existence_check::ExistenceCheckBenchmark;
+pub use key_type_any::KeyTypeAnyBenchmark;
+pub use key_type_copy::KeyTypeCopyBenchmark;
+pub use parallel_any_scatter::{DEFAULT_SCATTER_WAYS, ParallelAnyScatterBenchmark};
+pub use prepared_cached::PreparedCachedBenchmark;
+pub use raw_sql_large_in::{LiteralOrder, RawSqlLargeInBenchmark};
 pub use temp_table_any::TempTableAnyBenchmark;
 pub use temp_table_binary_copy::TempTableBinaryCopyBenchmark;
+pub use temp_table_binary_freeze::TempTableBinaryFreezeBenchmark;
 pub use temp_table_binary_no_index::TempTableBinaryNoIndexBenchmark;
+pub use temp_table_composite_copy::TempTableCompositeCopyBenchmark;
 pub use temp_table_join::TempTableJoinBenchmark;
 pub use temp_table_optimized_binary::TempTableOptimizedBinaryBenchmark;
+pub use temp_table_optimized_freeze::TempTableFreezeBenchmark;
+pub use temp_table_storage::{ColumnStorage, TempTableStorageBenchmark};
 pub use temp_table_text_copy::TempTableTextCopyBenchmark;
+pub use temp_table_uuid_join::TempTableUuidJoinBenchmark;
 pub use unnest_array::UnnestArrayBenchmark;
+pub use values_join::ValuesJoinBenchmark;
 
-use crate::BenchmarkTest;
+use crate::keys::KeyType;
+use crate::{BenchmarkContext, BenchmarkIds, BenchmarkResult, BenchmarkTest, ExampleData, utils};
+use rand::Rng;
+use rand::seq::SliceRandom;
 use std::sync::Arc;
 
+/// ID-set expression for query templates in the temp table benchmarks
+///
+/// # Arguments
+/// * `table` - The run's temp table, from `BenchmarkContext::unique_temp_name`
+fn temp_ids_expr(table: &str) -> String {
+    format!("(SELECT id FROM {})", table)
+}
+
+/// Stand-in for a run's temp table name in [`BenchmarkTest::rendered_query`]
+const TEMP_TABLE_PLACEHOLDER: &str = "temp_ids_<random>";
+
+/// Rendered final query of the temp table benchmarks that look the IDs up with
+/// `IN (SELECT id FROM <table>)`, or the query template in its place
+fn rendered_temp_in_query(context: &BenchmarkContext) -> String {
+    let ids_expr = temp_ids_expr(TEMP_TABLE_PLACEHOLDER);
+    let default_query = context.schema.lookup_query(&format!("IN {}", ids_expr));
+    context.render_query(&default_query, &ids_expr).into_owned()
+}
+
+/// Validation shared by the strategies that JOIN the lookup table against the IDs
+///
+/// Rows that don't carry their hash (e.g. the UUID join) skip the match against the
+/// requested IDs, as in [`utils::validate_results_match`].
+fn validate_join(ids: BenchmarkIds<'_>, results: &[ExampleData]) -> BenchmarkResult<()> {
+    let ids = ids.bytes()?;
+    utils::validate_results(results, ids.len())?;
+    utils::validate_results_match(results, &ids.iter().copied().collect())?;
+    // The JOIN must not fan out: each temp table row matches at most one override
+    utils::validate_no_duplicates(results)
+}
+
 /// Get all available benchmarks
 pub fn get_all_benchmarks() -> Vec<Arc<dyn BenchmarkTest>> {
     vec![
-        Arc::new(ChunkedPreparedBenchmark),
+        Arc::new(ChunkedPreparedBenchmark::default()),
+        Arc::new(ChunkedPreparedBenchmark::with_chunk_size(500)),
+        Arc::new(ChunkedPreparedBenchmark::with_chunk_size(10_000)),
+        Arc::new(ChunkedPreparedTxnBenchmark),
         Arc::new(AnyArrayBenchmark),
+        Arc::new(PreparedCachedBenchmark::default()),
         Arc::new(UnnestArrayBenchmark),
         Arc::new(TempTableTextCopyBenchmark),
         Arc::new(TempTableBinaryCopyBenchmark),
+        Arc::new(TempTableBinaryFreezeBenchmark),
         Arc::new(TempTableOptimizedBinaryBenchmark),
+        Arc::new(TempTableFreezeBenchmark),
         Arc::new(TempTableJoinBenchmark),
         Arc::new(TempTableAnyBenchmark),
-        Arc::new(RawSqlLargeInBenchmark),
+        Arc::new(RawSqlLargeInBenchmark::new(LiteralOrder::Insertion)),
+        Arc::new(RawSqlLargeInBenchmark::new(LiteralOrder::Sorted)),
         Arc::new(TempTableBinaryNoIndexBenchmark),
+        Arc::new(TempTableStorageBenchmark::new(ColumnStorage::Plain)),
+        Arc::new(TempTableStorageBenchmark::new(ColumnStorage::Main)),
+        Arc::new(TempTableStorageBenchmark::new(ColumnStorage::Extended)),
+        Arc::new(TempTableStorageBenchmark::new(ColumnStorage::External)),
+        Arc::new(TempTableCompositeCopyBenchmark),
+        Arc::new(KeyTypeAnyBenchmark::new(KeyType::Bytea)),
+        Arc::new(KeyTypeAnyBenchmark::new(KeyType::Bigint)),
+        Arc::new(KeyTypeAnyBenchmark::new(KeyType::Uuid)),
+        Arc::new(KeyTypeAnyBenchmark::new(KeyType::Timestamp)),
+        Arc::new(KeyTypeCopyBenchmark::new(KeyType::Bytea)),
+        Arc::new(KeyTypeCopyBenchmark::new(KeyType::Bigint)),
+        Arc::new(KeyTypeCopyBenchmark::new(KeyType::Uuid)),
+        Arc::new(KeyTypeCopyBenchmark::new(KeyType::Timestamp)),
+        Arc::new(TempTableUuidJoinBenchmark),
+        Arc::new(ExistenceCheckBenchmark),
+        Arc::new(CtePrefilterJoinBenchmark),
+        Arc::new(ParallelAnyScatterBenchmark),
+        Arc::new(ValuesJoinBenchmark),
     ]
 }
 
@@ -46,3 +131,441 @@ pub fn get_benchmark_by_name(name: &str) -> Option<Arc<dyn BenchmarkTest>> {
         .into_iter()
         .find(|benchmark| benchmark.name() == name)
 }
+
+/// Get every benchmark whose name matches a glob pattern, in suite order
+///
+/// `*` matches any run of characters and `?` any single character, so
+/// `temp_table_*` selects the temp table family. A pattern without either only
+/// matches the benchmark of that exact name.
+///
+/// # Arguments
+/// * `pattern` - Benchmark name or glob pattern
+///
+/// # Returns
+/// * `Vec<Arc<dyn BenchmarkTest>>` - Matching benchmarks, empty if none match
+pub fn get_benchmarks_matching(pattern: &str) -> Vec<Arc<dyn BenchmarkTest>> {
+    get_all_benchmarks()
+        .into_iter()
+        .filter(|benchmark| glob_matches(pattern.as_bytes(), benchmark.name().as_bytes()))
+        .collect()
+}
+
+/// Shuffle benchmarks into a random order drawn from `rng`
+///
+/// Whichever benchmark runs first pays for cold connections and caches, so a fixed
+/// order biases the same benchmark every run. Seeding `rng` from the run's seed keeps
+/// the order reproducible, and drawing again for each repeat varies it between repeats.
+///
+/// # Arguments
+/// * `benchmarks` - Benchmarks to reorder in place
+/// * `rng` - Random number generator to draw the order from
+pub fn shuffle_benchmarks<R: Rng + ?Sized>(benchmarks: &mut [Arc<dyn BenchmarkTest>], rng: &mut R) {
+    benchmarks.shuffle(rng);
+}
+
+/// Whether `name` matches the glob `pattern` of literal bytes, `*` and `?`
+fn glob_matches(pattern: &[u8], name: &[u8]) -> bool {
+    match pattern.split_first() {
+        None => name.is_empty(),
+        // Let the star take nothing, or one more byte and try again
+        Some((b'*', rest)) => {
+            glob_matches(rest, name) || (!name.is_empty() && glob_matches(pattern, &name[1..]))
+        }
+        Some((&expected, rest)) => match name.split_first() {
+            Some((&actual, name)) => {
+                (expected == b'?' || expected == actual) && glob_matches(rest, name)
+            }
+            None => false,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keys::Keys;
+    use crate::schema::SchemaConfig;
+    use crate::utils::{generate_ids_from_table, generate_test_ids};
+    use crate::{BenchmarkContext, BenchmarkResult, ExampleData, ID_RANGE, IdKind, IdSet};
+    use sqlx::postgres::PgPoolOptions;
+    use std::time::Duration;
+
+    /// Run a benchmark with the representation of `ids` it asks for, as the suite does
+    async fn run_with(
+        benchmark: &dyn BenchmarkTest,
+        context: &BenchmarkContext,
+        ids: &IdSet,
+    ) -> BenchmarkResult<Vec<ExampleData>> {
+        benchmark.run(context, ids.get(benchmark.id_kind())).await
+    }
+
+    /// Responses sorted, so row sets compare whatever order they came back in
+    fn sorted_responses(rows: Vec<ExampleData>) -> Vec<String> {
+        let mut responses: Vec<String> = rows.into_iter().map(|row| row.response).collect();
+        responses.sort_unstable();
+        responses
+    }
+
+    /// Context whose pool points at a closed port, so any database access fails fast
+    fn unreachable_context() -> BenchmarkContext {
+        let pool = PgPoolOptions::new()
+            .acquire_timeout(Duration::from_millis(200))
+            .connect_lazy("postgres://postgres@127.0.0.1:1/unreachable")
+            .expect("lazy pool from a valid URL");
+        BenchmarkContext::new(pool)
+    }
+
+    #[test]
+    fn shuffle_order_follows_the_seed() {
+        use rand::SeedableRng;
+        use rand::rngs::StdRng;
+
+        let order = |seed: u64| -> Vec<String> {
+            let mut benchmarks = get_all_benchmarks();
+            shuffle_benchmarks(&mut benchmarks, &mut StdRng::seed_from_u64(seed));
+            benchmarks
+                .iter()
+                .map(|benchmark| benchmark.name().to_string())
+                .collect()
+        };
+        assert_eq!(order(42), order(42));
+        assert_ne!(order(42), order(43));
+
+        // Only the order changes
+        let mut shuffled = order(42);
+        let mut all: Vec<String> = get_all_benchmarks()
+            .iter()
+            .map(|b| b.name().to_string())
+            .collect();
+        shuffled.sort_unstable();
+        all.sort_unstable();
+        assert_eq!(shuffled, all);
+    }
+
+    #[test]
+    fn temp_table_glob_selects_exactly_the_temp_table_family() {
+        let names = |benchmarks: Vec<Arc<dyn BenchmarkTest>>| -> Vec<String> {
+            benchmarks
+                .iter()
+                .map(|benchmark| benchmark.name().to_string())
+                .collect()
+        };
+        let temp_tables = names(get_benchmarks_matching("temp_table_*"));
+        let expected: Vec<String> = names(get_all_benchmarks())
+            .into_iter()
+            .filter(|name| name.starts_with("temp_table_"))
+            .collect();
+        assert_eq!(temp_tables.len(), 14);
+        assert_eq!(temp_tables, expected);
+        assert!(
+            get_benchmarks_matching("temp_table_*")
+                .iter()
+                .all(|benchmark| benchmark.uses_temp_table())
+        );
+
+        assert_eq!(names(get_benchmarks_matching("any_array")), ["any_array"]);
+        assert_eq!(
+            names(get_benchmarks_matching("*_storage_*ain")),
+            ["temp_table_storage_plain", "temp_table_storage_main"]
+        );
+        assert_eq!(
+            names(get_benchmarks_matching("chunked_prepared_???")),
+            ["chunked_prepared_500", "chunked_prepared_txn"]
+        );
+        assert!(get_benchmarks_matching("temp_table").is_empty());
+    }
+
+    #[tokio::test]
+    async fn every_benchmark_returns_no_rows_for_no_ids_without_the_database() {
+        let context = unreachable_context();
+        let ids = IdSet::new(Vec::new());
+        for benchmark in get_all_benchmarks() {
+            let result = run_with(benchmark.as_ref(), &context, &ids).await;
+            assert!(
+                matches!(&result, Ok(rows) if rows.is_empty()),
+                "{} with no IDs returned {:?}",
+                benchmark.name(),
+                result.map(|rows| rows.len()),
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn every_benchmark_accepts_generated_ids() {
+        let context = unreachable_context();
+        let ids = IdSet::new(generate_test_ids(10, ID_RANGE));
+        for benchmark in get_all_benchmarks() {
+            // Building the future is enough to type-check the call; it is never polled
+            let run = run_with(benchmark.as_ref(), &context, &ids);
+            drop(run);
+        }
+    }
+
+    #[test]
+    fn key_type_benchmarks_declare_their_key_and_derive_one_key_per_id() {
+        assert_eq!(AnyArrayBenchmark.id_kind(), IdKind::BigInt);
+        assert_eq!(AnyArrayBenchmark.key_type(), KeyType::Bigint);
+        assert_eq!(UnnestArrayBenchmark.id_kind(), IdKind::Bytes);
+        assert_eq!(UnnestArrayBenchmark.key_type(), KeyType::Bytea);
+
+        let ids = generate_test_ids(100, ID_RANGE);
+        for key_type in KeyType::ALL {
+            assert_eq!(KeyTypeAnyBenchmark::new(key_type).key_type(), key_type);
+            assert_eq!(KeyTypeCopyBenchmark::new(key_type).key_type(), key_type);
+
+            let keys = Keys::from_ids(key_type, &ids);
+            assert_eq!(keys.key_type(), key_type);
+            assert_eq!(keys.len(), ids.len());
+        }
+    }
+
+    #[test]
+    fn values_list_has_one_row_per_id() {
+        for count in [1, 3, 2_000] {
+            let values = values_join::build_values_list(count);
+            assert_eq!(values.matches("($").count(), count);
+            assert!(values.ends_with(&format!("(${}))", count)));
+        }
+        assert_eq!(values_join::build_values_list(2), "(VALUES ($1), ($2))");
+        // An ID set over the parameter limit splits into full chunks and a remainder
+        let ids = generate_test_ids(values_join::MAX_VALUES + 10, ID_RANGE);
+        let chunks: Vec<usize> = ids
+            .chunks(values_join::MAX_VALUES)
+            .map(<[_]>::len)
+            .collect();
+        assert_eq!(chunks, [values_join::MAX_VALUES, 10]);
+    }
+
+    #[tokio::test]
+    async fn built_queries_reference_the_configured_schema() {
+        let mut context = unreachable_context();
+        context.schema = SchemaConfig {
+            table: "app.lookups".to_string(),
+            id_column: "digest".to_string(),
+            value_column: "payload".to_string(),
+        };
+
+        assert_eq!(
+            chunked_prepared::build_prepared_query(&context, 2),
+            "SELECT payload AS response FROM app.lookups WHERE digest IN ($1, $2);"
+        );
+        let chunked = ChunkedPreparedBenchmark::with_chunk_size(3);
+        assert_eq!(chunked.name(), "chunked_prepared_3");
+        assert_eq!(
+            chunked_prepared::build_prepared_query(&context, chunked.max_values),
+            "SELECT payload AS response FROM app.lookups WHERE digest IN ($1, $2, $3);"
+        );
+        assert_eq!(
+            values_join::build_values_query(&context, 2),
+            "SELECT payload AS response FROM app.lookups JOIN (VALUES ($1), ($2)) AS v(id) ON app.lookups.digest = v.id;"
+        );
+        assert_eq!(
+            context.schema.join_query("temp_ids_1"),
+            "SELECT payload AS response FROM app.lookups JOIN temp_ids_1 ON app.lookups.digest = temp_ids_1.id;"
+        );
+    }
+
+    #[tokio::test]
+    async fn chunk_size_three_binds_exactly_three_placeholders() {
+        let context = unreachable_context();
+        let chunked = ChunkedPreparedBenchmark::with_chunk_size(3);
+        assert_eq!(chunked.max_values, 3);
+
+        // Ten IDs still bind three per statement
+        let query = chunked.rendered_query(&context, 10).unwrap();
+        assert_eq!(
+            query,
+            "SELECT response FROM overrides WHERE hash IN ($1, $2, $3);"
+        );
+    }
+
+    /// Needs a database: `DATABASE_URL=postgres://... cargo test -- --ignored`
+    #[tokio::test]
+    #[ignore]
+    async fn temp_table_benchmarks_run_concurrently_on_one_pool() {
+        let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        let pool = PgPoolOptions::new().connect(&url).await.unwrap();
+        let context = BenchmarkContext::new(pool);
+        let ids = IdSet::new(generate_test_ids(1_000, ID_RANGE));
+
+        // Two strategies plus a second copy of one, so two runs also share a benchmark
+        let (binary, text, binary_again) = tokio::join!(
+            run_with(&TempTableBinaryCopyBenchmark, &context, &ids),
+            run_with(&TempTableTextCopyBenchmark, &context, &ids),
+            run_with(&TempTableBinaryCopyBenchmark, &context, &ids),
+        );
+        let binary = binary.unwrap();
+        assert_eq!(text.unwrap().len(), binary.len());
+        assert_eq!(binary_again.unwrap().len(), binary.len());
+
+        context.drop_temp_tables().await.unwrap();
+    }
+
+    /// Needs a database: `DATABASE_URL=postgres://... cargo test -- --ignored`
+    #[tokio::test]
+    #[ignore]
+    async fn any_array_bigint_keys_return_the_same_rows_as_unnest_bytea_keys() {
+        let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        let pool = PgPoolOptions::new().connect(&url).await.unwrap();
+        let context = BenchmarkContext::new(pool);
+        // Keys from the table, so both strategies return rows to compare
+        let ids = IdSet::new(
+            generate_ids_from_table(&context.pool, &context.schema, 1_000, 0.5)
+                .await
+                .unwrap(),
+        );
+
+        let any = sorted_responses(run_with(&AnyArrayBenchmark, &context, &ids).await.unwrap());
+        let unnest = sorted_responses(
+            run_with(&UnnestArrayBenchmark, &context, &ids)
+                .await
+                .unwrap(),
+        );
+        assert!(!any.is_empty());
+        assert_eq!(any, unnest);
+    }
+
+    /// Needs a database: `DATABASE_URL=postgres://... cargo test -- --ignored`
+    #[tokio::test]
+    #[ignore]
+    async fn binary_freeze_returns_the_same_rows_as_binary_copy() {
+        let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        let pool = PgPoolOptions::new().connect(&url).await.unwrap();
+        let context = BenchmarkContext::new(pool);
+        let ids = IdSet::new(
+            generate_ids_from_table(&context.pool, &context.schema, 1_000, 0.5)
+                .await
+                .unwrap(),
+        );
+
+        let frozen = sorted_responses(
+            run_with(&TempTableBinaryFreezeBenchmark, &context, &ids)
+                .await
+                .unwrap(),
+        );
+        let copied = sorted_responses(
+            run_with(&TempTableBinaryCopyBenchmark, &context, &ids)
+                .await
+                .unwrap(),
+        );
+        assert!(!frozen.is_empty());
+        assert_eq!(frozen, copied);
+
+        context.drop_temp_tables().await.unwrap();
+    }
+
+    /// Needs a database: `DATABASE_URL=postgres://... cargo test -- --ignored`
+    #[tokio::test]
+    #[ignore]
+    async fn parallel_any_scatter_returns_the_same_rows_as_any_array() {
+        let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        let pool = PgPoolOptions::new().connect(&url).await.unwrap();
+        let mut context = BenchmarkContext::new(pool);
+        let mut hashes = generate_ids_from_table(&context.pool, &context.schema, 40, 0.5)
+            .await
+            .unwrap();
+        // Repeats, which must not match twice from different partitions
+        hashes.extend_from_within(..10);
+        let ids = IdSet::new(hashes);
+
+        let expected =
+            sorted_responses(run_with(&AnyArrayBenchmark, &context, &ids).await.unwrap());
+        assert_eq!(expected.len(), 20);
+        // One partition, uneven ones, and more partitions than distinct IDs
+        for ways in [1, 3, DEFAULT_SCATTER_WAYS, 64] {
+            context.scatter_ways = ways;
+            let scattered = run_with(&ParallelAnyScatterBenchmark, &context, &ids)
+                .await
+                .unwrap();
+            assert_eq!(sorted_responses(scattered), expected, "{} ways", ways);
+        }
+    }
+
+    /// Needs a database: `DATABASE_URL=postgres://... cargo test -- --ignored`
+    #[tokio::test]
+    #[ignore]
+    async fn prepared_cached_prepares_once_across_runs() {
+        use std::sync::atomic::Ordering;
+
+        let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        let pool = PgPoolOptions::new().connect(&url).await.unwrap();
+        let context = BenchmarkContext::new(pool);
+        let ids = IdSet::new(
+            generate_ids_from_table(&context.pool, &context.schema, 1_000, 0.5)
+                .await
+                .unwrap(),
+        );
+
+        let benchmark = PreparedCachedBenchmark::default();
+        benchmark.setup(&context).await.unwrap();
+        let expected = run_with(&AnyArrayBenchmark, &context, &ids)
+            .await
+            .unwrap()
+            .len();
+        for _ in 0..10 {
+            assert_eq!(
+                run_with(&benchmark, &context, &ids).await.unwrap().len(),
+                expected
+            );
+        }
+        assert_eq!(benchmark.prepares.load(Ordering::Relaxed), 1);
+
+        // Cleanup releases the statement, so the next run prepares a new one
+        benchmark.cleanup(&context).await.unwrap();
+        run_with(&benchmark, &context, &ids).await.unwrap();
+        assert_eq!(benchmark.prepares.load(Ordering::Relaxed), 2);
+    }
+
+    /// Needs a database (PostgreSQL 16+, for the column STORAGE clause):
+    /// `DATABASE_URL=postgres://... cargo test -- --ignored`
+    #[tokio::test]
+    #[ignore]
+    async fn optimized_freeze_loads_60k_ids_and_matches_the_unfrozen_load() {
+        let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        let pool = PgPoolOptions::new().connect(&url).await.unwrap();
+        let context = BenchmarkContext::new(pool);
+        let ids = IdSet::new(generate_test_ids(60_000, ID_RANGE));
+
+        let frozen = run_with(&TempTableFreezeBenchmark, &context, &ids)
+            .await
+            .unwrap();
+        let unfrozen = run_with(&TempTableOptimizedBinaryBenchmark, &context, &ids)
+            .await
+            .unwrap();
+        assert_eq!(frozen.len(), unfrozen.len());
+
+        context.drop_temp_tables().await.unwrap();
+    }
+
+    /// Needs a database: `DATABASE_URL=postgres://... cargo test --features mem-profiling -- --ignored`
+    #[cfg(feature = "mem-profiling")]
+    #[tokio::test]
+    #[ignore]
+    async fn raw_sql_allocates_more_client_memory_than_an_array_bind() {
+        use crate::executor::run_iteration;
+
+        let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        let pool = PgPoolOptions::new().connect(&url).await.unwrap();
+        let mut context = BenchmarkContext::new(pool);
+        context.disable_cache = false;
+        let ids = IdSet::new(generate_test_ids(20_000, ID_RANGE));
+
+        let mut peaks = Vec::new();
+        for benchmark in [
+            &RawSqlLargeInBenchmark::new(LiteralOrder::Insertion) as &dyn BenchmarkTest,
+            &AnyArrayBenchmark,
+        ] {
+            let ids = ids.get(benchmark.id_kind());
+            let iteration = run_iteration(&context, benchmark, ids, 1, context.retry).await;
+            iteration.result.unwrap();
+            peaks.push(iteration.peak_bytes.unwrap());
+        }
+        // Each hex literal alone is 69 bytes, against 32 bytes per bound ID
+        assert!(
+            peaks[0] > peaks[1],
+            "raw SQL {} vs ANY {}",
+            peaks[0],
+            peaks[1]
+        );
+    }
+}
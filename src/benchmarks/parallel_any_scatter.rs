@@ -0,0 +1,95 @@
+use crate::{
+    BenchmarkContext, BenchmarkError, BenchmarkIds, BenchmarkResult, BenchmarkTest, ExampleData,
+    SettingsConnection,
+};
+use async_trait::async_trait;
+use std::collections::HashSet;
+use tokio::task::JoinSet;
+use tracing::instrument;
+
+/// Default number of partitions queried concurrently
+pub const DEFAULT_SCATTER_WAYS: usize = 4;
+
+/// Condition on the key column matched on each partition
+const PREDICATE: &str = "= ANY($1)";
+
+/// Benchmark that splits `= ANY($1)` across several pool connections in parallel
+///
+/// The IDs are deduplicated, split into `scatter_ways` contiguous partitions, and
+/// each partition is queried on its own connection concurrently; the rows are
+/// concatenated in partition order. Disjoint partitions mean each hash is matched
+/// at most once, so the merged rows are exactly those of a single `any_array`
+/// query. It trades extra connections and the client-side merge for spreading
+/// the index lookups over several backends.
+pub struct ParallelAnyScatterBenchmark;
+
+#[async_trait]
+impl BenchmarkTest for ParallelAnyScatterBenchmark {
+    #[instrument(skip(self, context, ids), fields(ids_count = ids.len(), ways = context.scatter_ways))]
+    async fn run(
+        &self,
+        context: &BenchmarkContext,
+        ids: BenchmarkIds<'_>,
+    ) -> BenchmarkResult<Vec<ExampleData>> {
+        let ids = ids.bytes()?;
+        // No partitions to scatter, so don't check out any connections
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // Duplicate IDs split across partitions would match the same row twice
+        let mut seen = HashSet::with_capacity(ids.len());
+        let unique: Vec<[u8; 32]> = ids.iter().filter(|id| seen.insert(*id)).copied().collect();
+
+        let ways = context.scatter_ways.max(1);
+        let partition_size = unique.len().div_ceil(ways).max(1);
+
+        // Each task checks out its own connection, so partitions run on separate backends
+        let query = context.schema.lookup_query(PREDICATE);
+        let mut tasks = JoinSet::new();
+        for (index, partition) in unique.chunks(partition_size).enumerate() {
+            let pool = context.pool.clone();
+            let settings = context.local_settings.clone();
+            let backends = context.run_backends();
+            let partition = partition.to_vec();
+            let query = query.clone();
+            tasks.spawn(async move {
+                let mut conn = SettingsConnection::acquire(&pool, &settings).await?;
+                if let Some(backends) = backends {
+                    backends.track(&mut conn).await?;
+                }
+                let rows: Vec<ExampleData> = sqlx::query_as(&query)
+                    .bind(partition)
+                    .fetch_all(&mut *conn)
+                    .await?;
+                conn.finish().await?;
+                Ok::<_, BenchmarkError>((index, rows))
+            });
+        }
+
+        // Gather in completion order, then restore partition order
+        let mut partitions = Vec::with_capacity(ways);
+        while let Some(joined) = tasks.join_next().await {
+            let partition = joined.map_err(|e| BenchmarkError::BenchmarkFailed {
+                message: format!("scatter task failed: {}", e),
+            })??;
+            partitions.push(partition);
+        }
+        partitions.sort_unstable_by_key(|(index, _)| *index);
+
+        Ok(partitions.into_iter().flat_map(|(_, rows)| rows).collect())
+    }
+
+    fn rendered_query(&self, context: &BenchmarkContext, _num_ids: usize) -> Option<String> {
+        // Every partition runs the same query with its share of the IDs
+        Some(context.schema.lookup_query(PREDICATE))
+    }
+
+    fn name(&self) -> &'static str {
+        "parallel_any_scatter"
+    }
+
+    fn description(&self) -> &'static str {
+        "Splits the IDs across several connections, runs ANY($1) on each concurrently and merges"
+    }
+}
@@ -0,0 +1,161 @@
+use crate::explain::{ExplainOptions, collect_plan};
+use crate::{
+    BenchmarkContext, BenchmarkError, BenchmarkIds, BenchmarkResult, BenchmarkTest, ExampleData,
+};
+use async_trait::async_trait;
+use sqlx::pool::PoolConnection;
+use sqlx::postgres::PgStatement;
+use sqlx::{Connection, Executor, Postgres, Statement};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tokio::sync::Mutex;
+use tracing::{info, instrument};
+
+/// Condition on the key column matched by this benchmark
+const PREDICATE: &str = "= ANY($1)";
+
+/// A connection held across runs and the statement prepared on it
+struct PreparedLookup {
+    conn: PoolConnection<Postgres>,
+    statement: PgStatement<'static>,
+    /// Backend serving `conn`, for cancelling a timed-out run
+    pid: i32,
+}
+
+/// Benchmark that prepares the `ANY` lookup once and reuses it across iterations
+///
+/// `any_array` checks a connection out of the pool on every run, so which runs
+/// find the statement already parsed in sqlx's per-connection cache depends on the
+/// pool. This benchmark holds one connection from `setup` to `cleanup` and
+/// prepares the statement on it once, so every timed run skips parsing, and after
+/// five executions PostgreSQL may switch to a cached generic plan. Comparing the
+/// two shows what plan caching saves over cold planning.
+///
+/// The statement cache must be enabled: without it sqlx re-prepares every run.
+#[derive(Default)]
+pub struct PreparedCachedBenchmark {
+    lookup: Mutex<Option<PreparedLookup>>,
+    /// Statements prepared so far, one per `setup` unless a run had to prepare it
+    pub(super) prepares: AtomicUsize,
+}
+
+impl PreparedCachedBenchmark {
+    /// Hold a connection from the context's pool and prepare the lookup on it
+    ///
+    /// # Arguments
+    /// * `context` - Benchmark execution context
+    ///
+    /// # Returns
+    /// * `BenchmarkResult<PreparedLookup>` - The connection and its statement, or an
+    ///   error if the statement cache is disabled
+    async fn prepare(&self, context: &BenchmarkContext) -> BenchmarkResult<PreparedLookup> {
+        let mut conn = context.pool.acquire().await?;
+        let query = context.schema.lookup_query(PREDICATE);
+        let statement = Statement::to_owned(&conn.prepare(&query).await?);
+        if conn.cached_statements_size() == 0 {
+            return Err(BenchmarkError::Setup {
+                message: "the prepared statement cache is disabled, so every run would \
+                          prepare the statement again"
+                    .to_string(),
+            });
+        }
+        let pid = sqlx::query_scalar("SELECT pg_backend_pid();")
+            .fetch_one(&mut *conn)
+            .await?;
+        self.prepares.fetch_add(1, Ordering::Relaxed);
+        Ok(PreparedLookup {
+            conn,
+            statement,
+            pid,
+        })
+    }
+}
+
+#[async_trait]
+impl BenchmarkTest for PreparedCachedBenchmark {
+    #[instrument(skip(self, context, ids), fields(ids_count = ids.len()))]
+    async fn run(
+        &self,
+        context: &BenchmarkContext,
+        ids: BenchmarkIds<'_>,
+    ) -> BenchmarkResult<Vec<ExampleData>> {
+        let ids = ids.bytes()?;
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut lookup = self.lookup.lock().await;
+        // Only a run without `setup`, e.g. from `verify`, pays for preparing
+        if lookup.is_none() {
+            *lookup = Some(self.prepare(context).await?);
+        }
+        let PreparedLookup {
+            conn,
+            statement,
+            pid,
+        } = lookup.as_mut().expect("prepared above");
+        if let Some(backends) = context.run_backends() {
+            backends.add(*pid);
+        }
+
+        let query = statement.query_as::<ExampleData>().bind(ids);
+        let result = if context.local_settings.is_empty() {
+            query.fetch_all(&mut **conn).await
+        } else {
+            // SET LOCAL needs a transaction; the statement outlives its rollback
+            let mut transaction = conn.begin().await?;
+            crate::apply_settings(&mut transaction, &context.local_settings).await?;
+            let result = query.fetch_all(&mut *transaction).await;
+            transaction.rollback().await?;
+            result
+        };
+
+        result.map_err(BenchmarkError::Database)
+    }
+
+    async fn explain(
+        &self,
+        context: &BenchmarkContext,
+        ids: BenchmarkIds<'_>,
+        options: &ExplainOptions,
+    ) -> BenchmarkResult<Option<String>> {
+        let ids = ids.bytes()?;
+        let mut conn = context.connection().await?;
+        let rows = sqlx::query(&options.wrap(&context.schema.lookup_query(PREDICATE)))
+            .bind(ids)
+            .fetch_all(&mut *conn)
+            .await?;
+        conn.finish().await?;
+
+        Ok(Some(collect_plan(&rows)?))
+    }
+
+    fn rendered_query(&self, context: &BenchmarkContext, _num_ids: usize) -> Option<String> {
+        Some(context.schema.lookup_query(PREDICATE))
+    }
+
+    fn name(&self) -> &'static str {
+        "prepared_cached"
+    }
+
+    fn description(&self) -> &'static str {
+        "Prepares the ANY lookup once on a held connection and reuses it every run"
+    }
+
+    async fn setup(&self, context: &BenchmarkContext) -> BenchmarkResult<()> {
+        // A fresh statement per setup, since each target or sweep step has its own pool
+        let prepared = self.prepare(context).await?;
+        *self.lookup.lock().await = Some(prepared);
+        info!("Prepared the {} lookup", self.name());
+        Ok(())
+    }
+
+    async fn cleanup(&self, context: &BenchmarkContext) -> BenchmarkResult<()> {
+        // Return the held connection before clearing caches like every other benchmark
+        self.lookup.lock().await.take();
+        context.clear_caches().await
+    }
+
+    fn session_requirement(&self) -> Option<&'static str> {
+        Some("keeps a prepared statement on one server connection across runs")
+    }
+}
@@ -1,9 +1,36 @@
-use crate::{BenchmarkContext, BenchmarkError, BenchmarkResult, BenchmarkTest, ExampleData};
+use crate::explain::{ExplainOptions, collect_plan};
+use crate::utils::hash_test_id;
+use crate::{
+    BenchmarkContext, BenchmarkError, BenchmarkIds, BenchmarkResult, BenchmarkTest, ExampleData,
+};
 use async_trait::async_trait;
+use std::borrow::Cow;
 use tracing::instrument;
 
+/// Order of the literals in the IN clause
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LiteralOrder {
+    /// IDs in the order they were generated
+    Insertion,
+    /// IDs sorted bytewise, which is also the order of their hex literals
+    Sorted,
+}
+
 /// Benchmark that uses raw SQL with large IN clause
-pub struct RawSqlLargeInBenchmark;
+///
+/// The [`LiteralOrder::Sorted`] variant measures whether the order of the literals
+/// affects planning or execution. Sorting happens inside the timed run, since it is
+/// part of building the query.
+pub struct RawSqlLargeInBenchmark {
+    order: LiteralOrder,
+}
+
+impl RawSqlLargeInBenchmark {
+    /// Create a benchmark emitting the literals in the given order
+    pub fn new(order: LiteralOrder) -> Self {
+        Self { order }
+    }
+}
 
 #[async_trait]
 impl BenchmarkTest for RawSqlLargeInBenchmark {
@@ -11,40 +38,68 @@ impl BenchmarkTest for RawSqlLargeInBenchmark {
     async fn run(
         &self,
         context: &BenchmarkContext,
-        ids: &[[u8; 32]],
+        ids: BenchmarkIds<'_>,
     ) -> BenchmarkResult<Vec<ExampleData>> {
-        // Build the IN clause string directly to eliminate parameter binding overhead
-        let ids_str = ids
-            .iter()
-            .map(|id| {
-                // https://stackoverflow.com/questions/42117523/importing-bytea-data-into-postgresql-by-using-copy-from-stdin
-                // We need to format this entry as the string `\\x657B954D27B4AC56FA997D24A5FF2563`
-                format!("'\\x{}'", hex::encode(id))
-            })
-            .collect::<Vec<_>>()
-            .join(",");
-
-        // Construct the complete SQL query
-        let query = format!(
-            "SELECT response FROM overrides WHERE hash IN ({});",
-            ids_str
-        );
+        let ids = ids.bytes()?;
+        // `IN ()` is a syntax error, and an empty set matches nothing anyway
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let query = build_query(context, ids, self.order);
 
         // Execute the raw SQL query
+        let mut conn = context.connection().await?;
         let result: Vec<ExampleData> = sqlx::query_as(&query)
-            .fetch_all(&context.pool)
+            .fetch_all(&mut *conn)
             .await
             .map_err(BenchmarkError::Database)?;
+        conn.finish().await?;
 
         Ok(result)
     }
 
+    async fn explain(
+        &self,
+        context: &BenchmarkContext,
+        ids: BenchmarkIds<'_>,
+        options: &ExplainOptions,
+    ) -> BenchmarkResult<Option<String>> {
+        let ids = ids.bytes()?;
+        let mut conn = context.connection().await?;
+        let rows = sqlx::query(&options.wrap(&build_query(context, ids, self.order)))
+            .fetch_all(&mut *conn)
+            .await?;
+        conn.finish().await?;
+
+        Ok(Some(collect_plan(&rows)?))
+    }
+
+    fn rendered_query(&self, context: &BenchmarkContext, num_ids: usize) -> Option<String> {
+        let ids: Vec<[u8; 32]> = (1..=num_ids as i64).map(hash_test_id).collect();
+        Some(build_query(context, &ids, self.order))
+    }
+
     fn name(&self) -> &'static str {
-        "raw_sql_large_in"
+        match self.order {
+            LiteralOrder::Insertion => "raw_sql_large_in",
+            LiteralOrder::Sorted => "raw_sql_sorted_in",
+        }
     }
 
     fn description(&self) -> &'static str {
-        "Builds large IN clause as raw SQL string to eliminate network/parameter binding overhead"
+        match self.order {
+            LiteralOrder::Insertion => {
+                "Builds large IN clause as raw SQL string to eliminate network/parameter binding overhead"
+            }
+            LiteralOrder::Sorted => {
+                "Same as raw_sql_large_in but with the IN literals sorted, to measure literal-order impact"
+            }
+        }
+    }
+
+    fn supports_query_template(&self) -> bool {
+        true
     }
 
     async fn cleanup(&self, context: &BenchmarkContext) -> BenchmarkResult<()> {
@@ -53,3 +108,32 @@ impl BenchmarkTest for RawSqlLargeInBenchmark {
         Ok(())
     }
 }
+
+/// Build the complete SQL query with every ID inlined as a BYTEA literal
+fn build_query(context: &BenchmarkContext, ids: &[[u8; 32]], order: LiteralOrder) -> String {
+    let ids: Cow<[[u8; 32]]> = match order {
+        LiteralOrder::Insertion => Cow::Borrowed(ids),
+        LiteralOrder::Sorted => {
+            let mut sorted = ids.to_vec();
+            sorted.sort_unstable();
+            Cow::Owned(sorted)
+        }
+    };
+
+    // Build the IN clause string directly to eliminate parameter binding overhead
+    let ids_str = ids
+        .iter()
+        .map(|id| {
+            // https://stackoverflow.com/questions/42117523/importing-bytea-data-into-postgresql-by-using-copy-from-stdin
+            // We need to format this entry as the string `\\x657B954D27B4AC56FA997D24A5FF2563`
+            format!("'\\x{}'", hex::encode(id))
+        })
+        .collect::<Vec<_>>()
+        .join(",");
+
+    // Construct the complete SQL query
+    match &context.query_template {
+        Some(template) => template.render(&format!("({})", ids_str)),
+        None => context.schema.lookup_query(&format!("IN ({})", ids_str)),
+    }
+}
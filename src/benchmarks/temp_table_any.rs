@@ -1,6 +1,15 @@
-use crate::{BenchmarkContext, BenchmarkResult, BenchmarkTest, ExampleData};
+use super::TEMP_TABLE_PLACEHOLDER;
+use super::temp_table_join::load_temp_ids;
+use crate::explain::{ExplainOptions, collect_plan};
+use crate::schema::SchemaConfig;
+use crate::{BenchmarkContext, BenchmarkIds, BenchmarkResult, BenchmarkTest, ExampleData};
 use async_trait::async_trait;
 
+/// Query executed once the temp table is loaded
+fn query(schema: &SchemaConfig, table: &str) -> String {
+    schema.lookup_query(&format!("= ANY(SELECT id FROM {})", table))
+}
+
 /// Benchmark that uses temporary table with ANY operator subquery
 pub struct TempTableAnyBenchmark;
 
@@ -9,60 +18,24 @@ impl BenchmarkTest for TempTableAnyBenchmark {
     async fn run(
         &self,
         context: &BenchmarkContext,
-        ids: &[[u8; 32]],
+        ids: BenchmarkIds<'_>,
     ) -> BenchmarkResult<Vec<ExampleData>> {
-        let mut transaction = context.pool.begin().await?;
-
-        // Create optimized unlogged table with PLAIN storage
-        sqlx::query("CREATE UNLOGGED TABLE temp_ids (id BYTEA STORAGE PLAIN PRIMARY KEY);")
-            .execute(&mut *transaction)
-            .await?;
-
-        // Get a copy-in handle for the temporary table with binary format
-        let mut handle = transaction
-            .copy_in_raw("COPY temp_ids (id) FROM STDIN WITH (FORMAT BINARY)")
-            .await?;
-
-        // PostgreSQL binary format constants
-        const SIG: [u8; 19] = [
-            b'P', b'G', b'C', b'O', b'P', b'Y', b'\n', 0xFF, b'\r', b'\n', b'\0', 0x00, 0x00, 0x00,
-            0x00, 0x00, 0x00, 0x00, 0x00,
-        ];
-
-        // Binary format structure constants
-        const SIZE_PER_TUPLE: usize =
-            std::mem::size_of::<i16>() + std::mem::size_of::<u32>() + std::mem::size_of::<i64>();
-        const NUM_FIELDS_PER_TUPLE: i16 = 1;
-        const LENGTH_PER_FIELD: u32 = std::mem::size_of::<[u8; 32]>() as u32;
-
-        // Pre-allocate buffer with all data at once for optimal performance
-        let mut buf: Vec<u8> = Vec::with_capacity(
-            (ids.len() * SIZE_PER_TUPLE) + std::mem::size_of::<i16>() + SIG.len(),
-        );
-
-        // Add binary format header
-        buf.extend_from_slice(&SIG);
-
-        // Add all tuples to buffer
-        for id in ids.iter() {
-            buf.extend_from_slice(&NUM_FIELDS_PER_TUPLE.to_be_bytes());
-            buf.extend_from_slice(&LENGTH_PER_FIELD.to_be_bytes());
-            buf.extend_from_slice(id);
+        let ids = ids.bytes()?;
+        if ids.is_empty() {
+            return Ok(Vec::new());
         }
 
-        // Add end-of-data marker
-        buf.extend_from_slice(&(-1i16).to_be_bytes());
+        let table = context.unique_temp_name("temp_ids");
+        let mut transaction = context.begin().await?;
+        load_temp_ids(&mut transaction, &table, ids).await?;
 
-        // Send all data in one operation
-        handle.send(buf).await?;
-        handle.finish().await?;
+        // Apply any SET LOCAL overrides (e.g. planner toggles) before the query
+        context.apply_local_settings(&mut transaction).await?;
 
         // Use ANY operator with subquery for different query planning
-        let result: Vec<ExampleData> = sqlx::query_as(
-            "SELECT response FROM overrides WHERE hash = ANY(SELECT id FROM temp_ids);",
-        )
-        .fetch_all(&mut *transaction)
-        .await?;
+        let result: Vec<ExampleData> = sqlx::query_as(&query(&context.schema, &table))
+            .fetch_all(&mut *transaction)
+            .await?;
 
         // Rollback to clean up the temporary table
         transaction.rollback().await?;
@@ -70,6 +43,34 @@ impl BenchmarkTest for TempTableAnyBenchmark {
         Ok(result)
     }
 
+    async fn explain(
+        &self,
+        context: &BenchmarkContext,
+        ids: BenchmarkIds<'_>,
+        options: &ExplainOptions,
+    ) -> BenchmarkResult<Option<String>> {
+        let ids = ids.bytes()?;
+        // Plan the subquery against the loaded table, as in `run`
+        let table = context.unique_temp_name("temp_ids");
+        let mut transaction = context.begin().await?;
+        load_temp_ids(&mut transaction, &table, ids).await?;
+        context.apply_local_settings(&mut transaction).await?;
+        let rows = sqlx::query(&options.wrap(&query(&context.schema, &table)))
+            .fetch_all(&mut *transaction)
+            .await?;
+        transaction.rollback().await?;
+
+        Ok(Some(collect_plan(&rows)?))
+    }
+
+    fn uses_temp_table(&self) -> bool {
+        true
+    }
+
+    fn rendered_query(&self, context: &BenchmarkContext, _num_ids: usize) -> Option<String> {
+        Some(query(&context.schema, TEMP_TABLE_PLACEHOLDER))
+    }
+
     fn name(&self) -> &'static str {
         "temp_table_any"
     }
@@ -83,9 +84,7 @@ impl BenchmarkTest for TempTableAnyBenchmark {
         context.clear_caches().await?;
 
         // Clean up any leftover temp tables (just in case)
-        let _ = sqlx::query("DROP TABLE IF EXISTS temp_ids;")
-            .execute(&context.pool)
-            .await;
+        let _ = context.drop_temp_tables().await;
 
         Ok(())
     }
@@ -0,0 +1,97 @@
+use super::{rendered_temp_in_query, temp_ids_expr};
+use crate::copy::send_ids_streamed;
+use crate::{BenchmarkContext, BenchmarkIds, BenchmarkResult, BenchmarkTest, ExampleData};
+use async_trait::async_trait;
+use tracing::instrument;
+
+/// Bytes per COPY message, matching `temp_table_binary_copy`'s 4 KiB chunks
+const COPY_CHUNK_BYTES: usize = 4096;
+
+/// Benchmark that loads the temp table with `COPY ... WITH (FREEZE)`
+///
+/// Identical to `temp_table_binary_copy` apart from the `FREEZE` option, which
+/// writes the rows already frozen and marks the pages all-visible, saving the
+/// hint-bit writes on the first read. PostgreSQL only allows `FREEZE` when the
+/// table was created or truncated in the current (sub)transaction; here it is
+/// created in the same transaction just before the COPY.
+pub struct TempTableBinaryFreezeBenchmark;
+
+#[async_trait]
+impl BenchmarkTest for TempTableBinaryFreezeBenchmark {
+    #[instrument(skip(self, context, ids), fields(ids_count = ids.len()))]
+    async fn run(
+        &self,
+        context: &BenchmarkContext,
+        ids: BenchmarkIds<'_>,
+    ) -> BenchmarkResult<Vec<ExampleData>> {
+        let ids = ids.bytes()?;
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let table = context.unique_temp_name("temp_ids");
+        let mut transaction = context.begin().await?;
+
+        // Creating the table in this transaction is what makes FREEZE legal
+        sqlx::query(&format!(
+            "CREATE UNLOGGED TABLE {} (id BYTEA PRIMARY KEY);",
+            table
+        ))
+        .execute(&mut *transaction)
+        .await?;
+
+        let mut handle = transaction
+            .copy_in_raw(&format!(
+                "COPY {} (id) FROM STDIN WITH (FORMAT BINARY, FREEZE)",
+                table
+            ))
+            .await?;
+        send_ids_streamed(&mut handle, ids, COPY_CHUNK_BYTES).await?;
+        handle.finish().await?;
+
+        // Apply any SET LOCAL overrides (e.g. planner toggles) before the query
+        context.apply_local_settings(&mut transaction).await?;
+
+        // Perform the query using the temporary table
+        let default_query = context
+            .schema
+            .lookup_query(&format!("IN {}", temp_ids_expr(&table)));
+        let query = context.render_query(&default_query, &temp_ids_expr(&table));
+        let result: Vec<ExampleData> = sqlx::query_as(&query).fetch_all(&mut *transaction).await?;
+
+        // Rollback to clean up the temporary table
+        transaction.rollback().await?;
+
+        Ok(result)
+    }
+
+    fn uses_temp_table(&self) -> bool {
+        true
+    }
+
+    fn rendered_query(&self, context: &BenchmarkContext, _num_ids: usize) -> Option<String> {
+        Some(rendered_temp_in_query(context))
+    }
+
+    fn name(&self) -> &'static str {
+        "temp_table_binary_freeze"
+    }
+
+    fn description(&self) -> &'static str {
+        "Creates temporary table and loads it with binary COPY ... WITH (FREEZE)"
+    }
+
+    fn supports_query_template(&self) -> bool {
+        true
+    }
+
+    async fn cleanup(&self, context: &BenchmarkContext) -> BenchmarkResult<()> {
+        // Clear caches after the benchmark
+        context.clear_caches().await?;
+
+        // Clean up any leftover temp tables (just in case)
+        let _ = context.drop_temp_tables().await;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,110 @@
+use super::{TEMP_TABLE_PLACEHOLDER, validate_join};
+use crate::copy::BinaryCopyWriter;
+use crate::{BenchmarkContext, BenchmarkIds, BenchmarkResult, BenchmarkTest, ExampleData};
+use async_trait::async_trait;
+
+/// Encoded size of a `(id BYTEA, tenant_id BIGINT)` tuple
+const COMPOSITE_TUPLE_SIZE: usize =
+    BinaryCopyWriter::tuple_size(&[std::mem::size_of::<[u8; 32]>(), std::mem::size_of::<i64>()]);
+
+/// Benchmark that loads a two-column temporary table with binary COPY
+///
+/// Models a join key that carries an associated value (a tenant id) alongside the
+/// hash. `overrides` has no tenant column, so the tenant id is derived from the
+/// hash and only affects the COPY payload, not the join condition.
+pub struct TempTableCompositeCopyBenchmark;
+
+#[async_trait]
+impl BenchmarkTest for TempTableCompositeCopyBenchmark {
+    async fn run(
+        &self,
+        context: &BenchmarkContext,
+        ids: BenchmarkIds<'_>,
+    ) -> BenchmarkResult<Vec<ExampleData>> {
+        let ids = ids.bytes()?;
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let table = context.unique_temp_name("temp_ids");
+        let mut transaction = context.begin().await?;
+
+        // Create a two-column unlogged table keyed on the hash
+        sqlx::query(&format!("CREATE UNLOGGED TABLE {} (id BYTEA STORAGE PLAIN PRIMARY KEY, tenant_id BIGINT NOT NULL);", table))
+        .execute(&mut *transaction)
+        .await?;
+
+        // Get a copy-in handle for both columns with binary format
+        let mut handle = transaction
+            .copy_in_raw(&format!(
+                "COPY {} (id, tenant_id) FROM STDIN WITH (FORMAT BINARY)",
+                table
+            ))
+            .await?;
+
+        // Encode each ID with its derived tenant id
+        let mut writer = BinaryCopyWriter::with_capacity(2, ids.len(), COMPOSITE_TUPLE_SIZE);
+        for id in ids.iter() {
+            let tenant_id = tenant_id_for(id);
+            writer.write_tuple(&[id, &tenant_id]);
+        }
+
+        // Send all data in one operation
+        handle.send(writer.finish()).await?;
+        handle.finish().await?;
+
+        // Apply any SET LOCAL overrides (e.g. work_mem) before the join
+        context.apply_local_settings(&mut transaction).await?;
+
+        // Join on the hash; the tenant column rides along in the temp table
+        let result: Vec<ExampleData> = sqlx::query_as(&context.schema.join_query(&table))
+            .fetch_all(&mut *transaction)
+            .await?;
+
+        // Rollback to clean up the temporary table
+        transaction.rollback().await?;
+
+        Ok(result)
+    }
+
+    fn uses_temp_table(&self) -> bool {
+        true
+    }
+
+    fn rendered_query(&self, context: &BenchmarkContext, _num_ids: usize) -> Option<String> {
+        Some(context.schema.join_query(TEMP_TABLE_PLACEHOLDER))
+    }
+
+    fn name(&self) -> &'static str {
+        "temp_table_composite_copy"
+    }
+
+    fn description(&self) -> &'static str {
+        "Creates two-column temporary table (hash, tenant_id) with binary COPY and uses JOIN"
+    }
+
+    fn validate(&self, ids: BenchmarkIds<'_>, results: &[ExampleData]) -> BenchmarkResult<()> {
+        validate_join(ids, results)
+    }
+
+    fn tags(&self) -> &'static [&'static str] {
+        &["join"]
+    }
+
+    async fn cleanup(&self, context: &BenchmarkContext) -> BenchmarkResult<()> {
+        // Clear caches after the benchmark
+        context.clear_caches().await?;
+
+        // Clean up any leftover temp tables (just in case)
+        let _ = context.drop_temp_tables().await;
+
+        Ok(())
+    }
+}
+
+/// Derive a stable tenant id from the leading bytes of the hash
+fn tenant_id_for(id: &[u8; 32]) -> i64 {
+    let mut prefix = [0u8; 8];
+    prefix.copy_from_slice(&id[..8]);
+    i64::from_be_bytes(prefix) & 0xFFFF
+}
@@ -1,68 +1,85 @@
-use crate::{BenchmarkContext, BenchmarkResult, BenchmarkTest, ExampleData};
+use super::{TEMP_TABLE_PLACEHOLDER, validate_join};
+use crate::copy::{BinaryCopyWriter, ID_TUPLE_SIZE};
+use crate::explain::{ExplainOptions, collect_plan};
+use crate::schema::SchemaConfig;
+use crate::{BenchmarkContext, BenchmarkIds, BenchmarkResult, BenchmarkTest, ExampleData};
 use async_trait::async_trait;
+use sqlx::postgres::PgConnection;
+
+/// Query executed once the temp table is loaded
+fn query(schema: &SchemaConfig, table: &str) -> String {
+    schema.join_query(table)
+}
 
 /// Benchmark that uses temporary table with JOIN instead of IN
 pub struct TempTableJoinBenchmark;
 
+/// Create a PLAIN-storage temp table and fill it with one binary COPY
+///
+/// # Arguments
+/// * `conn` - Connection inside the benchmark's transaction
+/// * `table` - Name of the table to create
+/// * `ids` - IDs to copy in
+///
+/// # Returns
+/// * `BenchmarkResult<()>` - Success or database error
+pub(super) async fn load_temp_ids(
+    conn: &mut PgConnection,
+    table: &str,
+    ids: &[[u8; 32]],
+) -> BenchmarkResult<()> {
+    // Create optimized unlogged table with PLAIN storage
+    sqlx::query(&format!(
+        "CREATE UNLOGGED TABLE {} (id BYTEA STORAGE PLAIN PRIMARY KEY);",
+        table
+    ))
+    .execute(&mut *conn)
+    .await?;
+
+    // Get a copy-in handle for the temporary table with binary format
+    let mut handle = conn
+        .copy_in_raw(&format!(
+            "COPY {} (id) FROM STDIN WITH (FORMAT BINARY)",
+            table
+        ))
+        .await?;
+
+    // Encode all IDs into a single binary COPY payload
+    let mut writer = BinaryCopyWriter::with_capacity(1, ids.len(), ID_TUPLE_SIZE);
+    for id in ids.iter() {
+        writer.write_tuple(&[id]);
+    }
+
+    // Send all data in one operation
+    handle.send(writer.finish()).await?;
+    handle.finish().await?;
+
+    Ok(())
+}
+
 #[async_trait]
 impl BenchmarkTest for TempTableJoinBenchmark {
     async fn run(
         &self,
         context: &BenchmarkContext,
-        ids: &[[u8; 32]],
+        ids: BenchmarkIds<'_>,
     ) -> BenchmarkResult<Vec<ExampleData>> {
-        let mut transaction = context.pool.begin().await?;
-
-        // Create optimized unlogged table with PLAIN storage
-        sqlx::query("CREATE UNLOGGED TABLE temp_ids (id BYTEA STORAGE PLAIN PRIMARY KEY);")
-            .execute(&mut *transaction)
-            .await?;
-
-        // Get a copy-in handle for the temporary table with binary format
-        let mut handle = transaction
-            .copy_in_raw("COPY temp_ids (id) FROM STDIN WITH (FORMAT BINARY)")
-            .await?;
-
-        // PostgreSQL binary format constants
-        const SIG: [u8; 19] = [
-            b'P', b'G', b'C', b'O', b'P', b'Y', b'\n', 0xFF, b'\r', b'\n', b'\0', 0x00, 0x00, 0x00,
-            0x00, 0x00, 0x00, 0x00, 0x00,
-        ];
-
-        // Binary format structure constants
-        const SIZE_PER_TUPLE: usize =
-            std::mem::size_of::<i16>() + std::mem::size_of::<u32>() + std::mem::size_of::<i64>();
-        const NUM_FIELDS_PER_TUPLE: i16 = 1;
-        const LENGTH_PER_FIELD: u32 = std::mem::size_of::<[u32; 8]>() as u32;
-
-        // Pre-allocate buffer with all data at once for optimal performance
-        let mut buf: Vec<u8> = Vec::with_capacity(
-            (ids.len() * SIZE_PER_TUPLE) + std::mem::size_of::<i16>() + SIG.len(),
-        );
-
-        // Add binary format header
-        buf.extend_from_slice(&SIG);
-
-        // Add all tuples to buffer
-        for id in ids.iter() {
-            buf.extend_from_slice(&NUM_FIELDS_PER_TUPLE.to_be_bytes());
-            buf.extend_from_slice(&LENGTH_PER_FIELD.to_be_bytes());
-            buf.extend_from_slice(id);
+        let ids = ids.bytes()?;
+        if ids.is_empty() {
+            return Ok(Vec::new());
         }
 
-        // Add end-of-data marker
-        buf.extend_from_slice(&(-1i16).to_be_bytes());
+        let table = context.unique_temp_name("temp_ids");
+        let mut transaction = context.begin().await?;
+        load_temp_ids(&mut transaction, &table, ids).await?;
 
-        // Send all data in one operation
-        handle.send(buf).await?;
-        handle.finish().await?;
+        // Apply any SET LOCAL overrides (e.g. work_mem) before the join
+        context.apply_local_settings(&mut transaction).await?;
 
         // Use JOIN instead of IN for potentially better performance
-        let result: Vec<ExampleData> = sqlx::query_as(
-            "SELECT response FROM overrides JOIN temp_ids ON overrides.hash = temp_ids.id;",
-        )
-        .fetch_all(&mut *transaction)
-        .await?;
+        let result: Vec<ExampleData> = sqlx::query_as(&query(&context.schema, &table))
+            .fetch_all(&mut *transaction)
+            .await?;
 
         // Rollback to clean up the temporary table
         transaction.rollback().await?;
@@ -70,6 +87,34 @@ impl BenchmarkTest for TempTableJoinBenchmark {
         Ok(result)
     }
 
+    async fn explain(
+        &self,
+        context: &BenchmarkContext,
+        ids: BenchmarkIds<'_>,
+        options: &ExplainOptions,
+    ) -> BenchmarkResult<Option<String>> {
+        let ids = ids.bytes()?;
+        // Plan the join against the loaded table, as in `run`
+        let table = context.unique_temp_name("temp_ids");
+        let mut transaction = context.begin().await?;
+        load_temp_ids(&mut transaction, &table, ids).await?;
+        context.apply_local_settings(&mut transaction).await?;
+        let rows = sqlx::query(&options.wrap(&query(&context.schema, &table)))
+            .fetch_all(&mut *transaction)
+            .await?;
+        transaction.rollback().await?;
+
+        Ok(Some(collect_plan(&rows)?))
+    }
+
+    fn uses_temp_table(&self) -> bool {
+        true
+    }
+
+    fn rendered_query(&self, context: &BenchmarkContext, _num_ids: usize) -> Option<String> {
+        Some(query(&context.schema, TEMP_TABLE_PLACEHOLDER))
+    }
+
     fn name(&self) -> &'static str {
         "temp_table_join"
     }
@@ -78,14 +123,20 @@ impl BenchmarkTest for TempTableJoinBenchmark {
         "Creates temporary table with binary COPY and uses JOIN instead of IN clause"
     }
 
+    fn validate(&self, ids: BenchmarkIds<'_>, results: &[ExampleData]) -> BenchmarkResult<()> {
+        validate_join(ids, results)
+    }
+
+    fn tags(&self) -> &'static [&'static str] {
+        &["join"]
+    }
+
     async fn cleanup(&self, context: &BenchmarkContext) -> BenchmarkResult<()> {
         // Clear caches after the benchmark
         context.clear_caches().await?;
 
         // Clean up any leftover temp tables (just in case)
-        let _ = sqlx::query("DROP TABLE IF EXISTS temp_ids;")
-            .execute(&context.pool)
-            .await;
+        let _ = context.drop_temp_tables().await;
 
         Ok(())
     }
@@ -1,4 +1,6 @@
-use crate::{BenchmarkContext, BenchmarkResult, BenchmarkTest, ExampleData};
+use super::{rendered_temp_in_query, temp_ids_expr};
+use crate::copy::{BinaryCopyWriter, ID_TUPLE_SIZE};
+use crate::{BenchmarkContext, BenchmarkIds, BenchmarkResult, BenchmarkTest, ExampleData};
 use async_trait::async_trait;
 
 /// Benchmark that uses temporary table with optimized binary COPY
@@ -9,66 +11,51 @@ impl BenchmarkTest for TempTableOptimizedBinaryBenchmark {
     async fn run(
         &self,
         context: &BenchmarkContext,
-        ids: &[[u8; 32]],
+        ids: BenchmarkIds<'_>,
     ) -> BenchmarkResult<Vec<ExampleData>> {
-        let mut transaction = context.pool.begin().await?;
+        let ids = ids.bytes()?;
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let table = context.unique_temp_name("temp_ids");
+        let mut transaction = context.begin().await?;
 
         // Create optimized unlogged table with PLAIN storage
-        sqlx::query("CREATE UNLOGGED TABLE temp_ids (id BYTEA STORAGE PLAIN PRIMARY KEY);")
-            .execute(&mut *transaction)
-            .await?;
+        sqlx::query(&format!(
+            "CREATE UNLOGGED TABLE {} (id BYTEA STORAGE PLAIN PRIMARY KEY);",
+            table
+        ))
+        .execute(&mut *transaction)
+        .await?;
 
         // Get a copy-in handle for the temporary table with binary format
         let mut handle = transaction
-            .copy_in_raw("COPY temp_ids (id) FROM STDIN WITH (FORMAT BINARY)")
+            .copy_in_raw(&format!(
+                "COPY {} (id) FROM STDIN WITH (FORMAT BINARY)",
+                table
+            ))
             .await?;
 
-        // PostgreSQL binary format constants
-        const SIG: [u8; 19] = [
-            b'P', b'G', b'C', b'O', b'P', b'Y', b'\n', 0xFF, b'\r', b'\n', b'\0', 0x00, 0x00, 0x00,
-            0x00, 0x00, 0x00, 0x00, 0x00,
-        ];
-
-        // Binary format structure constants
-        const LENGTH_PER_FIELD: u32 = std::mem::size_of::<[u32; 8]>() as u32;
-        const SIZE_PER_TUPLE: usize =
-            std::mem::size_of::<i16>() + std::mem::size_of::<u32>() + LENGTH_PER_FIELD as usize;
-        const NUM_FIELDS_PER_TUPLE: i16 = 1;
-
-        // Pre-allocate buffer with all data at once for optimal performance
-        let mut buf: Vec<u8> = Vec::with_capacity(
-            (ids.len() * SIZE_PER_TUPLE) + std::mem::size_of::<i16>() + SIG.len(),
-        );
-
-        // Add binary format header
-        buf.extend_from_slice(&SIG);
-
-        // Add all tuples to buffer
+        // Encode all IDs into a single binary COPY payload
+        let mut writer = BinaryCopyWriter::with_capacity(1, ids.len(), ID_TUPLE_SIZE);
         for id in ids.iter() {
-            buf.extend_from_slice(&NUM_FIELDS_PER_TUPLE.to_be_bytes());
-            buf.extend_from_slice(&LENGTH_PER_FIELD.to_be_bytes());
-            buf.extend_from_slice(id);
+            writer.write_tuple(&[id]);
         }
 
-        // Add end-of-data marker
-        buf.extend_from_slice(&(-1i16).to_be_bytes());
-
-        // Verify buffer capacity was correctly calculated
-        assert_eq!(
-            buf.capacity(),
-            ids.len() * SIZE_PER_TUPLE + std::mem::size_of::<i16>() + SIG.len()
-        );
-
         // Send all data in one operation
-        handle.send(buf).await?;
+        handle.send(writer.finish()).await?;
         handle.finish().await?;
 
+        // Apply any SET LOCAL overrides (e.g. planner toggles) before the query
+        context.apply_local_settings(&mut transaction).await?;
+
         // Perform the query using the temporary table
-        let result: Vec<ExampleData> = sqlx::query_as(
-            "SELECT response FROM overrides WHERE hash IN (SELECT id FROM temp_ids);",
-        )
-        .fetch_all(&mut *transaction)
-        .await?;
+        let default_query = context
+            .schema
+            .lookup_query(&format!("IN {}", temp_ids_expr(&table)));
+        let query = context.render_query(&default_query, &temp_ids_expr(&table));
+        let result: Vec<ExampleData> = sqlx::query_as(&query).fetch_all(&mut *transaction).await?;
 
         // Rollback to clean up the temporary table
         transaction.rollback().await?;
@@ -76,6 +63,14 @@ impl BenchmarkTest for TempTableOptimizedBinaryBenchmark {
         Ok(result)
     }
 
+    fn uses_temp_table(&self) -> bool {
+        true
+    }
+
+    fn rendered_query(&self, context: &BenchmarkContext, _num_ids: usize) -> Option<String> {
+        Some(rendered_temp_in_query(context))
+    }
+
     fn name(&self) -> &'static str {
         "temp_table_optimized_binary"
     }
@@ -84,14 +79,16 @@ impl BenchmarkTest for TempTableOptimizedBinaryBenchmark {
         "Creates optimized temporary table with PLAIN storage and single-operation binary COPY"
     }
 
+    fn supports_query_template(&self) -> bool {
+        true
+    }
+
     async fn cleanup(&self, context: &BenchmarkContext) -> BenchmarkResult<()> {
         // Clear caches after the benchmark
         context.clear_caches().await?;
 
         // Clean up any leftover temp tables (just in case)
-        let _ = sqlx::query("DROP TABLE IF EXISTS temp_ids;")
-            .execute(&context.pool)
-            .await;
+        let _ = context.drop_temp_tables().await;
 
         Ok(())
     }
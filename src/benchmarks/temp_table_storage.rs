@@ -0,0 +1,146 @@
+use super::{rendered_temp_in_query, temp_ids_expr};
+use crate::copy::{BinaryCopyWriter, ID_TUPLE_SIZE};
+use crate::{BenchmarkContext, BenchmarkIds, BenchmarkResult, BenchmarkTest, ExampleData};
+use async_trait::async_trait;
+
+/// Column storage strategy applied to the temporary table's BYTEA column
+///
+/// 32-byte values sit well below the TOAST threshold, so these are expected to
+/// perform identically; the benchmarks exist to confirm that empirically.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnStorage {
+    Plain,
+    Main,
+    Extended,
+    External,
+}
+
+impl ColumnStorage {
+    /// SQL keyword used in the `STORAGE` clause
+    fn as_sql(self) -> &'static str {
+        match self {
+            ColumnStorage::Plain => "PLAIN",
+            ColumnStorage::Main => "MAIN",
+            ColumnStorage::Extended => "EXTENDED",
+            ColumnStorage::External => "EXTERNAL",
+        }
+    }
+}
+
+/// Benchmark that isolates the effect of the BYTEA column's STORAGE clause
+///
+/// Everything apart from the storage strategy matches `temp_table_binary_no_index`:
+/// an unlogged table without an index, loaded with a single binary COPY.
+pub struct TempTableStorageBenchmark {
+    storage: ColumnStorage,
+}
+
+impl TempTableStorageBenchmark {
+    /// Create a benchmark using the given column storage strategy
+    pub fn new(storage: ColumnStorage) -> Self {
+        Self { storage }
+    }
+}
+
+#[async_trait]
+impl BenchmarkTest for TempTableStorageBenchmark {
+    async fn run(
+        &self,
+        context: &BenchmarkContext,
+        ids: BenchmarkIds<'_>,
+    ) -> BenchmarkResult<Vec<ExampleData>> {
+        let ids = ids.bytes()?;
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let table = context.unique_temp_name("temp_ids");
+        let mut transaction = context.begin().await?;
+
+        // Create unlogged table with the storage strategy under test
+        sqlx::query(&format!(
+            "CREATE UNLOGGED TABLE {} (id BYTEA STORAGE {});",
+            table,
+            self.storage.as_sql()
+        ))
+        .execute(&mut *transaction)
+        .await?;
+
+        // Get a copy-in handle for the temporary table with binary format
+        let mut handle = transaction
+            .copy_in_raw(&format!(
+                "COPY {} (id) FROM STDIN WITH (FORMAT BINARY)",
+                table
+            ))
+            .await?;
+
+        // Encode all IDs into a single binary COPY payload
+        let mut writer = BinaryCopyWriter::with_capacity(1, ids.len(), ID_TUPLE_SIZE);
+        for id in ids.iter() {
+            writer.write_tuple(&[id]);
+        }
+
+        // Send all data in one operation
+        handle.send(writer.finish()).await?;
+        handle.finish().await?;
+
+        // Apply any SET LOCAL overrides (e.g. planner toggles) before the query
+        context.apply_local_settings(&mut transaction).await?;
+
+        // Perform the query using the temporary table
+        let default_query = context
+            .schema
+            .lookup_query(&format!("IN {}", temp_ids_expr(&table)));
+        let query = context.render_query(&default_query, &temp_ids_expr(&table));
+        let result: Vec<ExampleData> = sqlx::query_as(&query).fetch_all(&mut *transaction).await?;
+
+        // Rollback to clean up the temporary table
+        transaction.rollback().await?;
+
+        Ok(result)
+    }
+
+    fn uses_temp_table(&self) -> bool {
+        true
+    }
+
+    fn rendered_query(&self, context: &BenchmarkContext, _num_ids: usize) -> Option<String> {
+        Some(rendered_temp_in_query(context))
+    }
+
+    fn name(&self) -> &'static str {
+        match self.storage {
+            ColumnStorage::Plain => "temp_table_storage_plain",
+            ColumnStorage::Main => "temp_table_storage_main",
+            ColumnStorage::Extended => "temp_table_storage_extended",
+            ColumnStorage::External => "temp_table_storage_external",
+        }
+    }
+
+    fn description(&self) -> &'static str {
+        match self.storage {
+            ColumnStorage::Plain => "Temporary table without index using BYTEA STORAGE PLAIN",
+            ColumnStorage::Main => "Temporary table without index using BYTEA STORAGE MAIN",
+            ColumnStorage::Extended => "Temporary table without index using BYTEA STORAGE EXTENDED",
+            ColumnStorage::External => "Temporary table without index using BYTEA STORAGE EXTERNAL",
+        }
+    }
+
+    fn tags(&self) -> &'static [&'static str] {
+        &["storage"]
+    }
+
+    fn supports_query_template(&self) -> bool {
+        true
+    }
+
+    async fn cleanup(&self, context: &BenchmarkContext) -> BenchmarkResult<()> {
+        // Clear caches after the benchmark
+        context.clear_caches().await?;
+
+        // Clean up any leftover temp tables (just in case)
+        let _ = context.drop_temp_tables().await;
+
+        Ok(())
+    }
+}
@@ -1,62 +1,101 @@
-use crate::{BenchmarkContext, BenchmarkResult, BenchmarkTest, ExampleData};
+use super::{rendered_temp_in_query, temp_ids_expr};
+use crate::explain::{ExplainOptions, collect_plan};
+use crate::schema::SchemaConfig;
+use crate::{BenchmarkContext, BenchmarkIds, BenchmarkResult, BenchmarkTest, ExampleData};
 use async_trait::async_trait;
+use sqlx::postgres::PgConnection;
 use tracing::instrument;
 
+/// Bytes per text COPY line: `\\x`, 64 hex digits and the newline
+const TEXT_LINE_SIZE: usize = 3 + 64 + 1;
+
+/// Query executed once the temp table is loaded
+fn query(schema: &SchemaConfig, table: &str) -> String {
+    schema.lookup_query(&format!("IN {}", temp_ids_expr(table)))
+}
+
 /// Benchmark that uses temporary table with text COPY
 pub struct TempTableTextCopyBenchmark;
 
+/// Encode IDs as text COPY lines for a single BYTEA column
+///
+/// Each line is the BYTEA hex literal `\x<hex>`. Backslash is the text format's
+/// escape character, so the literal's own backslash is doubled on the wire.
+///
+/// # Arguments
+/// * `ids` - IDs to encode, one line each
+///
+/// # Returns
+/// * `Vec<u8>` - The COPY payload
+fn encode_text_copy(ids: &[[u8; 32]]) -> Vec<u8> {
+    let mut payload = Vec::with_capacity(ids.len() * TEXT_LINE_SIZE);
+    for id in ids {
+        payload.extend_from_slice(b"\\\\x");
+        payload.extend_from_slice(hex::encode(id).as_bytes());
+        payload.push(b'\n');
+    }
+    payload
+}
+
+/// Create a temp table and fill it with text COPY
+///
+/// # Arguments
+/// * `conn` - Connection inside the benchmark's transaction
+/// * `table` - Name of the table to create
+/// * `ids` - IDs to copy in
+///
+/// # Returns
+/// * `BenchmarkResult<()>` - Success or database error
+async fn copy_ids_as_text(
+    conn: &mut PgConnection,
+    table: &str,
+    ids: &[[u8; 32]],
+) -> BenchmarkResult<()> {
+    // Create a temporary unlogged table to hold the IDs
+    sqlx::query(&format!(
+        "CREATE UNLOGGED TABLE {} (id BYTEA PRIMARY KEY);",
+        table
+    ))
+    .execute(&mut *conn)
+    .await?;
+
+    // Get a copy-in handle for the temporary table (text is COPY's default format)
+    let mut handle = conn
+        .copy_in_raw(&format!("COPY {} (id) FROM STDIN", table))
+        .await?;
+
+    // Send the data to PostgreSQL
+    handle.send(encode_text_copy(ids)).await?;
+    handle.finish().await?;
+
+    Ok(())
+}
+
 #[async_trait]
 impl BenchmarkTest for TempTableTextCopyBenchmark {
     #[instrument(skip(self, context, ids), fields(ids_count = ids.len()))]
     async fn run(
         &self,
         context: &BenchmarkContext,
-        ids: &[[u8; 32]],
+        ids: BenchmarkIds<'_>,
     ) -> BenchmarkResult<Vec<ExampleData>> {
-        // Couldn't get this to work. :(
-        return Err(crate::BenchmarkError::Setup {
-            message: "COPY with text format not supported in this benchmark".to_string(),
-        });
+        let ids = ids.bytes()?;
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
 
-        let mut transaction = context.pool.begin().await?;
+        let table = context.unique_temp_name("temp_ids");
+        let mut transaction = context.begin().await?;
 
-        // Create a temporary unlogged table to hold the IDs
-        sqlx::query("CREATE UNLOGGED TABLE temp_ids (id BYTEA PRIMARY KEY);")
-            .execute(&mut *transaction)
-            .await?;
-
-        // Get a copy-in handle for the temporary table
-        let mut handle = transaction
-            .copy_in_raw("COPY temp_ids (id) FROM STDIN")
-            .await?;
+        copy_ids_as_text(&mut transaction, &table, ids).await?;
 
-        // Prepare the IDs as text format with newlines
-        let ids_as_bytes: Vec<u8> = ids
-            .iter()
-            .map(|id| {
-                // Reverse the byte order to get big-endian encoding
-                let id_be: Vec<u8> = id.iter().rev().copied().collect();
-                let id_hex = hex::encode(hex::encode(id_be).to_uppercase()).to_uppercase();
-                format!("\\x{}\n", id_hex)
-            })
-            .fold(
-                Vec::with_capacity(ids.len() * 30), //estimate...
-                |mut acc, id_str| {
-                    acc.extend(id_str.as_bytes());
-                    acc
-                },
-            );
-
-        // Send the data to PostgreSQL
-        handle.send(ids_as_bytes).await?;
-        handle.finish().await?;
+        // Apply any SET LOCAL overrides (e.g. planner toggles) before the query
+        context.apply_local_settings(&mut transaction).await?;
 
         // Perform the query using the temporary table
-        let result: Vec<ExampleData> = sqlx::query_as(
-            "SELECT response FROM overrides WHERE hash IN (SELECT id FROM temp_ids);",
-        )
-        .fetch_all(&mut *transaction)
-        .await?;
+        let default_query = query(&context.schema, &table);
+        let query = context.render_query(&default_query, &temp_ids_expr(&table));
+        let result: Vec<ExampleData> = sqlx::query_as(&query).fetch_all(&mut *transaction).await?;
 
         // Rollback to clean up the temporary table
         transaction.rollback().await?;
@@ -64,6 +103,36 @@ impl BenchmarkTest for TempTableTextCopyBenchmark {
         Ok(result)
     }
 
+    async fn explain(
+        &self,
+        context: &BenchmarkContext,
+        ids: BenchmarkIds<'_>,
+        options: &ExplainOptions,
+    ) -> BenchmarkResult<Option<String>> {
+        let ids = ids.bytes()?;
+        // Plan the query against the loaded table, as in `run`
+        let table = context.unique_temp_name("temp_ids");
+        let mut transaction = context.begin().await?;
+        copy_ids_as_text(&mut transaction, &table, ids).await?;
+        context.apply_local_settings(&mut transaction).await?;
+        let default_query = query(&context.schema, &table);
+        let query = context.render_query(&default_query, &temp_ids_expr(&table));
+        let rows = sqlx::query(&options.wrap(&query))
+            .fetch_all(&mut *transaction)
+            .await?;
+        transaction.rollback().await?;
+
+        Ok(Some(collect_plan(&rows)?))
+    }
+
+    fn uses_temp_table(&self) -> bool {
+        true
+    }
+
+    fn rendered_query(&self, context: &BenchmarkContext, _num_ids: usize) -> Option<String> {
+        Some(rendered_temp_in_query(context))
+    }
+
     fn name(&self) -> &'static str {
         "temp_table_text_copy"
     }
@@ -72,15 +141,68 @@ impl BenchmarkTest for TempTableTextCopyBenchmark {
         "Creates temporary table and uses COPY with text format"
     }
 
+    fn supports_query_template(&self) -> bool {
+        true
+    }
+
     async fn cleanup(&self, context: &BenchmarkContext) -> BenchmarkResult<()> {
         // Clear caches after the benchmark
         context.clear_caches().await?;
 
         // Clean up any leftover temp tables (just in case)
-        let _ = sqlx::query("DROP TABLE IF EXISTS temp_ids;")
-            .execute(&context.pool)
-            .await;
+        let _ = context.drop_temp_tables().await;
 
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ID_RANGE;
+    use crate::utils::generate_test_ids;
+    use sqlx::Connection;
+
+    #[test]
+    fn encodes_one_escaped_hex_literal_per_line() {
+        let mut id = [0u8; 32];
+        id[0] = 0xab;
+        id[31] = 0x01;
+        let payload = encode_text_copy(&[id]);
+
+        assert_eq!(payload.len(), TEXT_LINE_SIZE);
+        assert_eq!(
+            String::from_utf8(payload).unwrap(),
+            format!("\\\\xab{}01\n", "00".repeat(30))
+        );
+    }
+
+    /// Needs a database: `DATABASE_URL=postgres://... cargo test -- --ignored`
+    #[tokio::test]
+    #[ignore]
+    async fn text_copy_inserts_every_id() {
+        let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        let mut conn = PgConnection::connect(&url).await.unwrap();
+        let mut transaction = conn.begin().await.unwrap();
+
+        let ids = generate_test_ids(100, ID_RANGE);
+        copy_ids_as_text(&mut transaction, "temp_ids_text_copy_test", &ids)
+            .await
+            .unwrap();
+
+        let (count,): (i64,) = sqlx::query_as("SELECT count(*) FROM temp_ids_text_copy_test;")
+            .fetch_one(&mut *transaction)
+            .await
+            .unwrap();
+        let (round_trips,): (bool,) =
+            sqlx::query_as("SELECT bool_and(id = ANY($1)) FROM temp_ids_text_copy_test;")
+                .bind(&ids)
+                .fetch_one(&mut *transaction)
+                .await
+                .unwrap();
+        transaction.rollback().await.unwrap();
+
+        assert_eq!(count, 100);
+        assert!(round_trips, "copied IDs differ from the originals");
+    }
+}
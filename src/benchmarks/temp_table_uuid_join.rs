@@ -0,0 +1,161 @@
+use super::validate_join;
+use crate::copy::BinaryCopyWriter;
+use crate::explain::{ExplainOptions, collect_plan};
+use crate::keys::{KeyType, Keys};
+use crate::{BenchmarkContext, BenchmarkIds, BenchmarkResult, BenchmarkTest, ExampleData};
+use async_trait::async_trait;
+use sqlx::postgres::PgConnection;
+use tracing::instrument;
+
+/// Query executed once the temp table is loaded
+fn query(table: &str) -> String {
+    format!(
+        "SELECT response FROM {} JOIN {} ON {}.key = {}.key;",
+        KeyType::Uuid.table(),
+        table,
+        KeyType::Uuid.table(),
+        table
+    )
+}
+
+/// Benchmark that joins UUID keys loaded into a temp table against a UUID-keyed table
+///
+/// The UUID counterpart of `temp_table_join`: the same logical IDs as 16-byte UUIDs
+/// instead of 32-byte hashes, so the two compare the cost of loading and joining on
+/// each key type. `key_type_any_uuid` covers the `= ANY($1)` lookup over the same table.
+pub struct TempTableUuidJoinBenchmark;
+
+/// Create a temp table of UUID keys and fill it with one binary COPY
+///
+/// # Arguments
+/// * `conn` - Connection inside the benchmark's transaction
+/// * `table` - Name of the table to create
+/// * `ids` - IDs to copy in, converted to their UUID keys
+///
+/// # Returns
+/// * `BenchmarkResult<()>` - Success or database error
+async fn load_temp_uuids(
+    conn: &mut PgConnection,
+    table: &str,
+    ids: &[[u8; 32]],
+) -> BenchmarkResult<()> {
+    let keys = Keys::from_ids(KeyType::Uuid, ids);
+
+    sqlx::query(&format!(
+        "CREATE UNLOGGED TABLE {} (key UUID PRIMARY KEY);",
+        table
+    ))
+    .execute(&mut *conn)
+    .await?;
+
+    // Get a copy-in handle for the temporary table with binary format
+    let mut handle = conn
+        .copy_in_raw(&format!(
+            "COPY {} (key) FROM STDIN WITH (FORMAT BINARY)",
+            table
+        ))
+        .await?;
+
+    // Encode each UUID in its 16-byte wire format
+    let mut writer = BinaryCopyWriter::with_capacity(
+        1,
+        keys.len(),
+        BinaryCopyWriter::tuple_size(&[KeyType::Uuid.encoded_len()]),
+    );
+    keys.write_copy(&mut writer);
+
+    // Send all data in one operation
+    handle.send(writer.finish()).await?;
+    handle.finish().await?;
+
+    Ok(())
+}
+
+#[async_trait]
+impl BenchmarkTest for TempTableUuidJoinBenchmark {
+    #[instrument(skip(self, context, ids), fields(ids_count = ids.len()))]
+    async fn run(
+        &self,
+        context: &BenchmarkContext,
+        ids: BenchmarkIds<'_>,
+    ) -> BenchmarkResult<Vec<ExampleData>> {
+        let ids = ids.bytes()?;
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let table = context.unique_temp_name("temp_uuids");
+        let mut transaction = context.begin().await?;
+        load_temp_uuids(&mut transaction, &table, ids).await?;
+
+        // Apply any SET LOCAL overrides (e.g. work_mem) before the join
+        context.apply_local_settings(&mut transaction).await?;
+
+        let result: Vec<ExampleData> = sqlx::query_as(&query(&table))
+            .fetch_all(&mut *transaction)
+            .await?;
+
+        // Rollback to clean up the temporary table
+        transaction.rollback().await?;
+
+        Ok(result)
+    }
+
+    async fn explain(
+        &self,
+        context: &BenchmarkContext,
+        ids: BenchmarkIds<'_>,
+        options: &ExplainOptions,
+    ) -> BenchmarkResult<Option<String>> {
+        let ids = ids.bytes()?;
+        // Plan the join against the loaded table, as in `run`
+        let table = context.unique_temp_name("temp_uuids");
+        let mut transaction = context.begin().await?;
+        load_temp_uuids(&mut transaction, &table, ids).await?;
+        context.apply_local_settings(&mut transaction).await?;
+        let rows = sqlx::query(&options.wrap(&query(&table)))
+            .fetch_all(&mut *transaction)
+            .await?;
+        transaction.rollback().await?;
+
+        Ok(Some(collect_plan(&rows)?))
+    }
+
+    fn uses_temp_table(&self) -> bool {
+        true
+    }
+
+    fn rendered_query(&self, _context: &BenchmarkContext, _num_ids: usize) -> Option<String> {
+        Some(query("temp_uuids_<random>"))
+    }
+
+    fn name(&self) -> &'static str {
+        "temp_table_uuid_join"
+    }
+
+    fn description(&self) -> &'static str {
+        "Binary COPY of UUID keys into a temp table, then JOIN on a UUID key"
+    }
+
+    fn validate(&self, ids: BenchmarkIds<'_>, results: &[ExampleData]) -> BenchmarkResult<()> {
+        validate_join(ids, results)
+    }
+
+    fn tags(&self) -> &'static [&'static str] {
+        &["join", "keytype"]
+    }
+
+    fn key_type(&self) -> KeyType {
+        KeyType::Uuid
+    }
+
+    async fn cleanup(&self, context: &BenchmarkContext) -> BenchmarkResult<()> {
+        // Clear caches after the benchmark
+        context.clear_caches().await?;
+
+        // Clean up any leftover temp tables (just in case)
+        let _ = context.drop_temp_tables().await;
+
+        Ok(())
+    }
+}
@@ -1,7 +1,16 @@
-use crate::{BenchmarkContext, BenchmarkError, BenchmarkResult, BenchmarkTest, ExampleData};
+use crate::explain::{ExplainOptions, collect_plan};
+use crate::{
+    BenchmarkContext, BenchmarkError, BenchmarkIds, BenchmarkResult, BenchmarkTest, ExampleData,
+};
 use async_trait::async_trait;
 use tracing::instrument;
 
+/// Condition on the key column matched by this benchmark
+const PREDICATE: &str = "IN (SELECT UNNEST($1))";
+
+/// ID-set expression substituted into a query template
+const IDS_EXPR: &str = "(SELECT UNNEST($1))";
+
 /// Benchmark that uses UNNEST function with array parameter
 pub struct UnnestArrayBenchmark;
 
@@ -11,18 +20,50 @@ impl BenchmarkTest for UnnestArrayBenchmark {
     async fn run(
         &self,
         context: &BenchmarkContext,
-        ids: &[[u8; 32]],
+        ids: BenchmarkIds<'_>,
     ) -> BenchmarkResult<Vec<ExampleData>> {
-        let result: Vec<ExampleData> =
-            sqlx::query_as("SELECT response FROM overrides WHERE hash IN (SELECT UNNEST($1));")
-                .bind(ids)
-                .fetch_all(&context.pool)
-                .await
-                .map_err(BenchmarkError::Database)?;
+        let ids = ids.bytes()?;
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let default_query = context.schema.lookup_query(PREDICATE);
+        let query = context.render_query(&default_query, IDS_EXPR);
+        let mut conn = context.connection().await?;
+        let result: Vec<ExampleData> = sqlx::query_as(&query)
+            .bind(ids)
+            .fetch_all(&mut *conn)
+            .await
+            .map_err(BenchmarkError::Database)?;
+        conn.finish().await?;
 
         Ok(result)
     }
 
+    async fn explain(
+        &self,
+        context: &BenchmarkContext,
+        ids: BenchmarkIds<'_>,
+        options: &ExplainOptions,
+    ) -> BenchmarkResult<Option<String>> {
+        let ids = ids.bytes()?;
+        let default_query = context.schema.lookup_query(PREDICATE);
+        let query = context.render_query(&default_query, IDS_EXPR);
+        let mut conn = context.connection().await?;
+        let rows = sqlx::query(&options.wrap(&query))
+            .bind(ids)
+            .fetch_all(&mut *conn)
+            .await?;
+        conn.finish().await?;
+
+        Ok(Some(collect_plan(&rows)?))
+    }
+
+    fn rendered_query(&self, context: &BenchmarkContext, _num_ids: usize) -> Option<String> {
+        let default_query = context.schema.lookup_query(PREDICATE);
+        Some(context.render_query(&default_query, IDS_EXPR).into_owned())
+    }
+
     fn name(&self) -> &'static str {
         "unnest_array"
     }
@@ -30,4 +71,8 @@ impl BenchmarkTest for UnnestArrayBenchmark {
     fn description(&self) -> &'static str {
         "Uses PostgreSQL's UNNEST function to convert array to table"
     }
+
+    fn supports_query_template(&self) -> bool {
+        true
+    }
 }
@@ -0,0 +1,115 @@
+use crate::explain::{ExplainOptions, collect_plan};
+use crate::{
+    BenchmarkContext, BenchmarkError, BenchmarkIds, BenchmarkResult, BenchmarkTest, ExampleData,
+};
+use async_trait::async_trait;
+use tracing::instrument;
+
+/// Maximum number of values per statement: PostgreSQL's limit on bind parameters
+pub(super) const MAX_VALUES: usize = 65_535;
+
+/// Benchmark that joins the table against a `VALUES` list of bound IDs
+///
+/// The IDs become the rows of an inline `(VALUES ($1), ($2), ...)` relation, which
+/// the planner can hash or merge join like `temp_table_join`'s temp table, but
+/// without creating and loading a table. ID sets over [`MAX_VALUES`] are split
+/// into one statement per chunk, all on the same connection.
+pub struct ValuesJoinBenchmark;
+
+#[async_trait]
+impl BenchmarkTest for ValuesJoinBenchmark {
+    #[instrument(skip(self, context, ids), fields(ids_count = ids.len()))]
+    async fn run(
+        &self,
+        context: &BenchmarkContext,
+        ids: BenchmarkIds<'_>,
+    ) -> BenchmarkResult<Vec<ExampleData>> {
+        let ids = ids.bytes()?;
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut conn = context.connection().await?;
+        let mut all_overrides = Vec::new();
+        for chunk in ids.chunks(MAX_VALUES) {
+            let query = build_values_query(context, chunk.len());
+            let mut query_builder = sqlx::query_as(&query);
+            for id in chunk.iter() {
+                query_builder = query_builder.bind(*id);
+            }
+            let overrides: Vec<ExampleData> = query_builder
+                .fetch_all(&mut *conn)
+                .await
+                .map_err(BenchmarkError::Database)?;
+            all_overrides.extend(overrides);
+        }
+        conn.finish().await?;
+
+        Ok(all_overrides)
+    }
+
+    async fn explain(
+        &self,
+        context: &BenchmarkContext,
+        ids: BenchmarkIds<'_>,
+        options: &ExplainOptions,
+    ) -> BenchmarkResult<Option<String>> {
+        let ids = ids.bytes()?;
+        // Every chunk shares the same shape, so the first one is representative
+        let Some(chunk) = ids.chunks(MAX_VALUES).next() else {
+            return Ok(None);
+        };
+
+        let query = options.wrap(&build_values_query(context, chunk.len()));
+        let mut query_builder = sqlx::query(&query);
+        for id in chunk.iter() {
+            query_builder = query_builder.bind(*id);
+        }
+
+        let mut conn = context.connection().await?;
+        let rows = query_builder.fetch_all(&mut *conn).await?;
+        conn.finish().await?;
+
+        Ok(Some(collect_plan(&rows)?))
+    }
+
+    fn rendered_query(&self, context: &BenchmarkContext, num_ids: usize) -> Option<String> {
+        // The first chunk's; later chunks share its shape
+        Some(build_values_query(context, num_ids.min(MAX_VALUES)))
+    }
+
+    fn name(&self) -> &'static str {
+        "values_join"
+    }
+
+    fn description(&self) -> &'static str {
+        "Joins against an inline VALUES list of bound IDs"
+    }
+
+    fn supports_query_template(&self) -> bool {
+        true
+    }
+}
+
+/// Build a `VALUES` list with one single-column row per placeholder
+///
+/// # Arguments
+/// * `num_values` - Number of rows (and placeholders)
+///
+/// # Returns
+/// * `String` - `(VALUES ($1), ($2), ...)`
+pub(super) fn build_values_list(num_values: usize) -> String {
+    let rows: Vec<String> = (1..=num_values).map(|i| format!("(${})", i)).collect();
+    format!("(VALUES {})", rows.join(", "))
+}
+
+/// Build the join against a `VALUES` list of `num_values` placeholders
+///
+/// A query template gets the list as its ID set, i.e. `IN (VALUES ...)`.
+pub(super) fn build_values_query(context: &BenchmarkContext, num_values: usize) -> String {
+    let values = build_values_list(num_values);
+    match &context.query_template {
+        Some(template) => template.render(&values),
+        None => context.schema.values_join_query(&values),
+    }
+}
@@ -0,0 +1,537 @@
+//! A/B comparison of two result directories
+//!
+//! Point the suite at a reference build and at the candidate build (e.g. two git
+//! checkouts, each run with its own `--csv-dir`), then compare the two directories.
+//! Each benchmark's raw run durations are compared with a two-sided Mann-Whitney U
+//! test, which makes no normality assumption and tolerates the long right tail of
+//! query latencies. The effect size is the rank-biserial correlation: the fraction of
+//! (baseline, candidate) run pairs where the candidate was faster minus the fraction
+//! where it was slower.
+//!
+//! A run can also be checked against a baseline `summary.csv` as it finishes: each
+//! benchmark's median is compared with the baseline's, and a median more than a
+//! threshold above it counts as a regression.
+
+use crate::utils::{get_raw_results_csv_path, get_summary_csv_path};
+use crate::{BenchmarkError, BenchmarkResult, BenchmarkStats};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+/// Run durations per benchmark, as read from one result directory
+pub type RunSet = BTreeMap<String, Vec<Duration>>;
+
+/// Default median increase, in percent, above which a benchmark counts as regressed
+pub const DEFAULT_MAX_REGRESSION_PCT: f64 = 10.0;
+
+/// Default significance level of the Mann-Whitney U test
+pub const SIGNIFICANCE_ALPHA: f64 = 0.05;
+
+/// Baseline medians per benchmark name and input size
+pub type BaselineMedians = BTreeMap<(String, usize), Duration>;
+
+/// Read every benchmark's run durations from a directory's `raw_results.csv`
+///
+/// # Arguments
+/// * `csv_dir` - Result directory written by a previous run (`--csv-dir`)
+///
+/// # Returns
+/// * `BenchmarkResult<RunSet>` - Durations per benchmark name, or an error if the file is missing or malformed
+pub fn load_runs(csv_dir: &Path) -> BenchmarkResult<RunSet> {
+    let path = get_raw_results_csv_path(csv_dir);
+    let malformed = |message: String| BenchmarkError::Setup {
+        message: format!("{}: {}", path.display(), message),
+    };
+    let contents = fs::read_to_string(&path).map_err(|e| malformed(e.to_string()))?;
+
+    let mut lines = contents.lines();
+    let header: Vec<&str> = lines
+        .next()
+        .ok_or_else(|| malformed("file is empty".to_string()))?
+        .split(',')
+        .collect();
+    let column = |name: &str| {
+        header
+            .iter()
+            .position(|c| *c == name)
+            .ok_or_else(|| malformed(format!("missing `{}` column", name)))
+    };
+    let name_column = column("benchmark_name")?;
+    let duration_column = column("duration_ns")?;
+
+    let mut runs = RunSet::new();
+    for (line_number, line) in lines.enumerate() {
+        if line.is_empty() {
+            continue;
+        }
+        let fields: Vec<&str> = line.split(',').collect();
+        let (Some(name), Some(duration)) = (fields.get(name_column), fields.get(duration_column))
+        else {
+            return Err(malformed(format!("line {} is truncated", line_number + 2)));
+        };
+        let nanos: u64 = duration.parse().map_err(|_| {
+            malformed(format!(
+                "line {} has an invalid duration `{}`",
+                line_number + 2,
+                duration
+            ))
+        })?;
+        runs.entry(name.to_string())
+            .or_default()
+            .push(Duration::from_nanos(nanos));
+    }
+
+    Ok(runs)
+}
+
+/// Read every benchmark's median from a `summary.csv`
+///
+/// Uses the nanosecond `median_ns` column, falling back to `median_ms` for files
+/// written before it existed.
+///
+/// # Arguments
+/// * `baseline` - A `summary.csv`, or a result directory containing one
+///
+/// # Returns
+/// * `BenchmarkResult<BaselineMedians>` - Medians per benchmark and input size, or an error if the file is missing or malformed
+pub fn load_baseline_medians(baseline: &Path) -> BenchmarkResult<BaselineMedians> {
+    let path = if baseline.is_dir() {
+        get_summary_csv_path(baseline)
+    } else {
+        baseline.to_path_buf()
+    };
+    let malformed = |message: String| BenchmarkError::Setup {
+        message: format!("{}: {}", path.display(), message),
+    };
+    let contents = fs::read_to_string(&path).map_err(|e| malformed(e.to_string()))?;
+
+    let mut lines = contents.lines();
+    let header: Vec<&str> = lines
+        .next()
+        .ok_or_else(|| malformed("file is empty".to_string()))?
+        .split(',')
+        .collect();
+    let column = |name: &str| header.iter().position(|c| *c == name);
+    let name_column = column("benchmark_name")
+        .ok_or_else(|| malformed("missing `benchmark_name` column".to_string()))?;
+    let size_column =
+        column("input_size").ok_or_else(|| malformed("missing `input_size` column".to_string()))?;
+    let (median_column, nanos_per_unit) = match (column("median_ns"), column("median_ms")) {
+        (Some(column), _) => (column, 1),
+        (None, Some(column)) => (column, 1_000_000),
+        (None, None) => return Err(malformed("missing `median_ns` column".to_string())),
+    };
+
+    let mut medians = BaselineMedians::new();
+    for (line_number, line) in lines.enumerate() {
+        if line.is_empty() {
+            continue;
+        }
+        let fields: Vec<&str> = line.split(',').collect();
+        let (Some(name), Some(size), Some(median)) = (
+            fields.get(name_column),
+            fields.get(size_column),
+            fields.get(median_column),
+        ) else {
+            return Err(malformed(format!("line {} is truncated", line_number + 2)));
+        };
+        let invalid = |what: &str, value: &str| {
+            malformed(format!(
+                "line {} has an invalid {} `{}`",
+                line_number + 2,
+                what,
+                value
+            ))
+        };
+        let size: usize = size.parse().map_err(|_| invalid("input size", size))?;
+        let median: u64 = median.parse().map_err(|_| invalid("median", median))?;
+        medians.insert(
+            (name.to_string(), size),
+            Duration::from_nanos(median.saturating_mul(nanos_per_unit)),
+        );
+    }
+
+    Ok(medians)
+}
+
+/// One benchmark's median against the baseline
+#[derive(Debug, Clone)]
+pub struct BaselineComparison {
+    pub name: String,
+    pub input_size: usize,
+    /// `None` if the baseline didn't run this benchmark at this input size
+    pub baseline_median: Option<Duration>,
+    pub current_median: Duration,
+    /// Change in median, in percent of the baseline median
+    pub change_pct: Option<f64>,
+}
+
+impl BaselineComparison {
+    /// Whether the median rose by more than `max_regression_pct` percent
+    pub fn is_regression(&self, max_regression_pct: f64) -> bool {
+        self.change_pct
+            .is_some_and(|change| change > max_regression_pct)
+    }
+}
+
+/// Compare each benchmark's median with a baseline `summary.csv`
+///
+/// # Arguments
+/// * `current` - Statistics of the run just completed
+/// * `baseline_csv` - A `summary.csv`, or a result directory containing one
+///
+/// # Returns
+/// * `BenchmarkResult<Vec<BaselineComparison>>` - One entry per current benchmark, in order
+pub fn compare_to_baseline(
+    current: &[BenchmarkStats],
+    baseline_csv: &Path,
+) -> BenchmarkResult<Vec<BaselineComparison>> {
+    Ok(compare_to_medians(
+        current,
+        &load_baseline_medians(baseline_csv)?,
+    ))
+}
+
+/// Compare each benchmark's median with already loaded baseline medians
+///
+/// # Arguments
+/// * `current` - Statistics of the run just completed
+/// * `baseline` - Medians from [`load_baseline_medians`]
+///
+/// # Returns
+/// * `Vec<BaselineComparison>` - One entry per current benchmark, in order
+pub fn compare_to_medians(
+    current: &[BenchmarkStats],
+    baseline: &BaselineMedians,
+) -> Vec<BaselineComparison> {
+    current
+        .iter()
+        .map(|stats| {
+            let current_median = stats.median();
+            let baseline_median = baseline
+                .get(&(stats.name.clone(), stats.input_size))
+                .copied();
+            let change_pct = baseline_median.filter(|base| !base.is_zero()).map(|base| {
+                (current_median.as_secs_f64() - base.as_secs_f64()) / base.as_secs_f64() * 100.0
+            });
+            BaselineComparison {
+                name: stats.name.clone(),
+                input_size: stats.input_size,
+                baseline_median,
+                current_median,
+                change_pct,
+            }
+        })
+        .collect()
+}
+
+/// Result of a two-sided Mann-Whitney U test
+#[derive(Debug, Clone, Copy)]
+pub struct MannWhitney {
+    /// U statistic of the first sample
+    pub u: f64,
+    /// Two-sided p-value from the normal approximation with tie correction
+    pub p_value: f64,
+    /// Rank-biserial correlation in `[-1, 1]`; positive when the second sample tends to be smaller
+    pub effect_size: f64,
+}
+
+/// Two-sided Mann-Whitney U test of `a` against `b`
+///
+/// Uses the normal approximation with a tie correction and continuity correction,
+/// which is accurate for the sample sizes the suite produces (tens of runs and up).
+///
+/// # Arguments
+/// * `a` - First sample (the baseline)
+/// * `b` - Second sample (the candidate)
+///
+/// # Returns
+/// * `Option<MannWhitney>` - `None` if either sample is empty
+pub fn mann_whitney<T: PartialOrd + Copy>(a: &[T], b: &[T]) -> Option<MannWhitney> {
+    if a.is_empty() || b.is_empty() {
+        return None;
+    }
+    let n1 = a.len() as f64;
+    let n2 = b.len() as f64;
+
+    // Rank the pooled sample, giving ties their average rank
+    let mut pooled: Vec<(T, bool)> = a
+        .iter()
+        .map(|&d| (d, true))
+        .chain(b.iter().map(|&d| (d, false)))
+        .collect();
+    pooled.sort_by(|x, y| x.0.partial_cmp(&y.0).unwrap_or(std::cmp::Ordering::Equal));
+
+    let mut rank_sum_a = 0.0;
+    let mut tie_term = 0.0;
+    let mut i = 0;
+    while i < pooled.len() {
+        let mut j = i;
+        while j < pooled.len() && pooled[j].0 == pooled[i].0 {
+            j += 1;
+        }
+        // Ranks are 1-based: positions i..j share the average of i+1..=j
+        let average_rank = (i + 1 + j) as f64 / 2.0;
+        let from_a = pooled[i..j].iter().filter(|(_, in_a)| *in_a).count();
+        rank_sum_a += average_rank * from_a as f64;
+        let tied = (j - i) as f64;
+        tie_term += tied * tied * tied - tied;
+        i = j;
+    }
+
+    let u = rank_sum_a - n1 * (n1 + 1.0) / 2.0;
+    let mean = n1 * n2 / 2.0;
+    let n = n1 + n2;
+    let variance = n1 * n2 / 12.0 * ((n + 1.0) - tie_term / (n * (n - 1.0)).max(1.0));
+    let p_value = if variance <= 0.0 {
+        1.0
+    } else {
+        let z = ((u - mean).abs() - 0.5).max(0.0) / variance.sqrt();
+        (2.0 * (1.0 - standard_normal_cdf(z))).min(1.0)
+    };
+
+    Some(MannWhitney {
+        u,
+        p_value,
+        effect_size: 2.0 * u / (n1 * n2) - 1.0,
+    })
+}
+
+/// Approximate p-value that two benchmarks' run durations differ only by chance
+///
+/// # Arguments
+/// * `a` - First benchmark
+/// * `b` - Second benchmark
+///
+/// # Returns
+/// * `f64` - Two-sided p-value of [`mann_whitney`] on the two sets of runs; 1.0 if
+///   either benchmark has no runs
+pub fn mann_whitney_u(a: &BenchmarkStats, b: &BenchmarkStats) -> f64 {
+    mann_whitney(&a.runs, &b.runs).map_or(1.0, |test| test.p_value)
+}
+
+/// Standard normal CDF via the complementary error function
+fn standard_normal_cdf(z: f64) -> f64 {
+    1.0 - 0.5 * erfc(z / std::f64::consts::SQRT_2)
+}
+
+/// Complementary error function (Numerical Recipes `erfcc`, fractional error below 1.2e-7)
+fn erfc(x: f64) -> f64 {
+    let z = x.abs();
+    let t = 1.0 / (1.0 + 0.5 * z);
+    let r = t
+        * (-z * z - 1.265_512_23
+            + t * (1.000_023_68
+                + t * (0.374_091_96
+                    + t * (0.096_784_18
+                        + t * (-0.186_288_06
+                            + t * (0.278_868_07
+                                + t * (-1.135_203_98
+                                    + t * (1.488_515_87
+                                        + t * (-0.822_152_23 + t * 0.170_872_77)))))))))
+            .exp();
+    if x >= 0.0 { r } else { 2.0 - r }
+}
+
+/// Outcome of comparing one benchmark across the two result sets
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    /// The candidate is significantly faster
+    Faster,
+    /// The candidate is significantly slower
+    Slower,
+    /// No significant difference at the chosen significance level
+    NoChange,
+    /// Only the baseline ran this benchmark
+    BaselineOnly,
+    /// Only the candidate ran this benchmark
+    CandidateOnly,
+}
+
+impl Verdict {
+    /// Label used in the report
+    pub fn label(&self) -> &'static str {
+        match self {
+            Verdict::Faster => "faster",
+            Verdict::Slower => "SLOWER",
+            Verdict::NoChange => "no change",
+            Verdict::BaselineOnly => "baseline only",
+            Verdict::CandidateOnly => "candidate only",
+        }
+    }
+}
+
+/// One benchmark's A/B comparison
+#[derive(Debug, Clone)]
+pub struct Comparison {
+    pub name: String,
+    pub baseline_median: Option<Duration>,
+    pub candidate_median: Option<Duration>,
+    /// Change in median, in percent of the baseline median
+    pub median_delta_pct: Option<f64>,
+    /// Test result, if both sides have runs
+    pub test: Option<MannWhitney>,
+    pub verdict: Verdict,
+}
+
+/// Compare every benchmark present in either result set
+///
+/// # Arguments
+/// * `baseline` - Runs of the reference build
+/// * `candidate` - Runs of the build under test
+/// * `alpha` - Significance level, e.g. `0.05`
+///
+/// # Returns
+/// * `Vec<Comparison>` - One entry per benchmark, sorted by name
+pub fn compare_runs(baseline: &RunSet, candidate: &RunSet, alpha: f64) -> Vec<Comparison> {
+    let mut names: Vec<&String> = baseline.keys().chain(candidate.keys()).collect();
+    names.sort();
+    names.dedup();
+
+    names
+        .into_iter()
+        .map(|name| {
+            let a = baseline.get(name).map(Vec::as_slice).unwrap_or_default();
+            let b = candidate.get(name).map(Vec::as_slice).unwrap_or_default();
+            let baseline_median = median(a);
+            let candidate_median = median(b);
+            let median_delta_pct = match (baseline_median, candidate_median) {
+                (Some(base), Some(cand)) if !base.is_zero() => {
+                    Some((cand.as_secs_f64() - base.as_secs_f64()) / base.as_secs_f64() * 100.0)
+                }
+                _ => None,
+            };
+            let test = mann_whitney(a, b);
+            let verdict = match (&test, a.is_empty(), b.is_empty()) {
+                (_, false, true) => Verdict::BaselineOnly,
+                (_, true, false) => Verdict::CandidateOnly,
+                (Some(test), _, _) if test.p_value < alpha => {
+                    if test.effect_size > 0.0 {
+                        Verdict::Faster
+                    } else {
+                        Verdict::Slower
+                    }
+                }
+                _ => Verdict::NoChange,
+            };
+
+            Comparison {
+                name: name.clone(),
+                baseline_median,
+                candidate_median,
+                median_delta_pct,
+                test,
+                verdict,
+            }
+        })
+        .collect()
+}
+
+/// Median of a sample, `None` if it is empty
+fn median(runs: &[Duration]) -> Option<Duration> {
+    if runs.is_empty() {
+        return None;
+    }
+    let mut stats = BenchmarkStats::new(String::new(), String::new(), 0);
+    stats.runs = runs.to_vec();
+    Some(stats.median())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats(name: &str, median_us: u64) -> BenchmarkStats {
+        let mut stats = BenchmarkStats::new(name.to_string(), String::new(), 60_000);
+        stats.runs = vec![Duration::from_micros(median_us)];
+        stats
+    }
+
+    fn with_runs(name: &str, runs_us: impl IntoIterator<Item = u64>) -> BenchmarkStats {
+        let mut stats = BenchmarkStats::new(name.to_string(), String::new(), 60_000);
+        stats.runs = runs_us.into_iter().map(Duration::from_micros).collect();
+        stats
+    }
+
+    #[test]
+    fn separated_runs_are_significant_and_identical_runs_are_not() {
+        // Interleaved ties within each sample; every run of `fast` beats every run of `slow`
+        let fast = with_runs("any_array", (0..50).map(|i| 1_000 + i % 10));
+        let slow = with_runs("unnest_array", (0..50).map(|i| 2_000 + i % 10));
+        let p = mann_whitney_u(&fast, &slow);
+        assert!(p < 1e-10, "p = {}", p);
+        assert_eq!(mann_whitney_u(&slow, &fast), p);
+
+        let same = with_runs("any_array_again", (0..50).map(|i| 1_000 + i % 10));
+        let p = mann_whitney_u(&fast, &same);
+        assert!(p > 0.99, "p = {}", p);
+
+        assert_eq!(mann_whitney_u(&fast, &with_runs("empty", [])), 1.0);
+    }
+
+    /// Write a baseline summary with one row per `(name, median_ns)`
+    fn baseline(dir: &Path, rows: &[(&str, u64)]) -> std::path::PathBuf {
+        let path = dir.join("summary.csv");
+        let mut contents =
+            String::from("benchmark_name,description,input_size,median_ms,median_ns\n");
+        for (name, median_ns) in rows {
+            contents.push_str(&format!(
+                "{},test,60000,{},{}\n",
+                name,
+                median_ns / 1_000_000,
+                median_ns
+            ));
+        }
+        fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn flags_regressions_beyond_the_threshold() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = baseline(
+            dir.path(),
+            &[("any_array", 10_000_000), ("unnest_array", 10_000_000)],
+        );
+        let current = vec![
+            stats("any_array", 8_000),     // 20% faster
+            stats("unnest_array", 12_000), // 20% slower
+            stats("existence_check", 5_000),
+        ];
+
+        let comparisons = compare_to_baseline(&current, &path).unwrap();
+        assert_eq!(comparisons.len(), 3);
+
+        let improved = &comparisons[0];
+        assert!((improved.change_pct.unwrap() + 20.0).abs() < 1e-9);
+        assert!(!improved.is_regression(DEFAULT_MAX_REGRESSION_PCT));
+
+        let regressed = &comparisons[1];
+        assert!((regressed.change_pct.unwrap() - 20.0).abs() < 1e-9);
+        assert!(regressed.is_regression(DEFAULT_MAX_REGRESSION_PCT));
+        assert!(!regressed.is_regression(25.0));
+
+        let new = &comparisons[2];
+        assert_eq!(new.baseline_median, None);
+        assert_eq!(new.change_pct, None);
+        assert!(!new.is_regression(DEFAULT_MAX_REGRESSION_PCT));
+    }
+
+    #[test]
+    fn falls_back_to_millisecond_medians() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("summary.csv");
+        fs::write(
+            &path,
+            "benchmark_name,input_size,median_ms\nany_array,60000,12\n",
+        )
+        .unwrap();
+
+        let medians = load_baseline_medians(dir.path()).unwrap();
+        assert_eq!(
+            medians.get(&("any_array".to_string(), 60_000)),
+            Some(&Duration::from_millis(12))
+        );
+    }
+}
@@ -0,0 +1,346 @@
+//! PostgreSQL binary COPY encoding
+//!
+//! The binary COPY stream is a fixed header, followed by one tuple per row
+//! (a 16-bit field count, then a 32-bit length prefix and the big-endian payload
+//! for each field), terminated by a field count of `-1`.
+
+use crate::BenchmarkResult;
+use crate::keys::PG_EPOCH_UNIX_MICROS;
+use chrono::NaiveDateTime;
+use sqlx::postgres::{PgConnection, PgCopyIn};
+use sqlx::types::Uuid;
+use std::ops::DerefMut;
+
+/// Signature, flags and header extension length that start every binary COPY stream
+pub const BINARY_COPY_HEADER: [u8; 19] = [
+    b'P', b'G', b'C', b'O', b'P', b'Y', b'\n', 0xFF, b'\r', b'\n', b'\0', 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00,
+];
+
+/// End-of-data marker written in place of a tuple's field count
+pub const BINARY_COPY_TRAILER: [u8; 2] = (-1i16).to_be_bytes();
+
+/// Encoded size of a single-column tuple holding one 32-byte ID
+pub const ID_TUPLE_SIZE: usize = BinaryCopyWriter::tuple_size(&[std::mem::size_of::<[u8; 32]>()]);
+
+/// Default size of each flush when streaming a COPY payload
+pub const DEFAULT_COPY_FLUSH_BYTES: usize = 1024 * 1024;
+
+/// Default ID count above which benchmarks stream their COPY payload
+pub const DEFAULT_COPY_STREAM_THRESHOLD: usize = 1_000_000;
+
+/// A value that can be written as one field of a binary COPY tuple
+pub trait CopyField {
+    /// Length of the encoded payload in bytes (excluding the length prefix)
+    fn payload_len(&self) -> usize;
+
+    /// Append the length prefix and payload to the buffer
+    fn encode(&self, buf: &mut Vec<u8>);
+}
+
+impl<const N: usize> CopyField for [u8; N] {
+    fn payload_len(&self) -> usize {
+        N
+    }
+
+    fn encode(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(&(N as u32).to_be_bytes());
+        buf.extend_from_slice(self);
+    }
+}
+
+impl CopyField for i64 {
+    fn payload_len(&self) -> usize {
+        std::mem::size_of::<i64>()
+    }
+
+    fn encode(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(&(std::mem::size_of::<i64>() as u32).to_be_bytes());
+        buf.extend_from_slice(&self.to_be_bytes());
+    }
+}
+
+impl CopyField for i32 {
+    fn payload_len(&self) -> usize {
+        std::mem::size_of::<i32>()
+    }
+
+    fn encode(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(&(std::mem::size_of::<i32>() as u32).to_be_bytes());
+        buf.extend_from_slice(&self.to_be_bytes());
+    }
+}
+
+impl CopyField for Uuid {
+    fn payload_len(&self) -> usize {
+        std::mem::size_of::<Uuid>()
+    }
+
+    fn encode(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(&(std::mem::size_of::<Uuid>() as u32).to_be_bytes());
+        buf.extend_from_slice(self.as_bytes());
+    }
+}
+
+/// `TEXT` is sent as its UTF-8 bytes
+impl CopyField for String {
+    fn payload_len(&self) -> usize {
+        self.len()
+    }
+
+    fn encode(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(&(self.len() as u32).to_be_bytes());
+        buf.extend_from_slice(self.as_bytes());
+    }
+}
+
+/// `TIMESTAMP` is sent as a 64-bit count of microseconds since 2000-01-01 00:00:00
+impl CopyField for NaiveDateTime {
+    fn payload_len(&self) -> usize {
+        std::mem::size_of::<i64>()
+    }
+
+    fn encode(&self, buf: &mut Vec<u8>) {
+        let micros = self.and_utc().timestamp_micros() - PG_EPOCH_UNIX_MICROS;
+        buf.extend_from_slice(&(std::mem::size_of::<i64>() as u32).to_be_bytes());
+        buf.extend_from_slice(&micros.to_be_bytes());
+    }
+}
+
+/// Builds a complete binary COPY payload in memory
+pub struct BinaryCopyWriter {
+    buf: Vec<u8>,
+    num_fields: i16,
+}
+
+impl BinaryCopyWriter {
+    /// Create a writer for tuples with `num_fields` fields
+    pub fn new(num_fields: i16) -> Self {
+        Self::with_capacity(num_fields, 0, 0)
+    }
+
+    /// Create a writer with room for `tuples` tuples of `tuple_size` bytes each
+    ///
+    /// # Arguments
+    /// * `num_fields` - Number of fields in every tuple
+    /// * `tuples` - Expected number of tuples
+    /// * `tuple_size` - Encoded size of one tuple, see [`BinaryCopyWriter::tuple_size`]
+    pub fn with_capacity(num_fields: i16, tuples: usize, tuple_size: usize) -> Self {
+        let mut buf = Vec::with_capacity(
+            BINARY_COPY_HEADER.len() + tuples * tuple_size + BINARY_COPY_TRAILER.len(),
+        );
+        buf.extend_from_slice(&BINARY_COPY_HEADER);
+        Self { buf, num_fields }
+    }
+
+    /// Encoded size of a tuple whose fields have the given payload lengths
+    pub const fn tuple_size(payload_lens: &[usize]) -> usize {
+        let mut size = std::mem::size_of::<i16>();
+        let mut i = 0;
+        while i < payload_lens.len() {
+            size += std::mem::size_of::<u32>() + payload_lens[i];
+            i += 1;
+        }
+        size
+    }
+
+    /// Append one tuple
+    ///
+    /// # Panics
+    /// Panics if the number of fields doesn't match the writer's field count
+    pub fn write_tuple(&mut self, fields: &[&dyn CopyField]) {
+        assert_eq!(
+            fields.len(),
+            self.num_fields as usize,
+            "tuple field count must match the COPY column list"
+        );
+
+        self.buf.extend_from_slice(&self.num_fields.to_be_bytes());
+        for field in fields {
+            field.encode(&mut self.buf);
+        }
+    }
+
+    /// Number of bytes written so far, including the header
+    pub fn len(&self) -> usize {
+        self.buf.len()
+    }
+
+    /// Whether nothing beyond the header has been written
+    pub fn is_empty(&self) -> bool {
+        self.buf.len() == BINARY_COPY_HEADER.len()
+    }
+
+    /// Take the bytes written since the writer was created or last taken from
+    ///
+    /// Lets a payload be sent in several flushes: the header goes out with the first
+    /// one, and [`finish`](Self::finish) returns only what was written after the last.
+    pub fn take(&mut self) -> Vec<u8> {
+        let capacity = self.buf.capacity();
+        std::mem::replace(&mut self.buf, Vec::with_capacity(capacity))
+    }
+
+    /// Append the end-of-data marker and return the finished payload
+    pub fn finish(mut self) -> Vec<u8> {
+        self.buf.extend_from_slice(&BINARY_COPY_TRAILER);
+        self.buf
+    }
+}
+
+/// Stream IDs as a single-column binary COPY payload in fixed-size flushes
+///
+/// Client memory stays at about `flush_bytes` whatever the number of IDs, instead
+/// of the whole payload being built up front.
+///
+/// # Arguments
+/// * `handle` - Copy-in handle for a binary COPY into a single `BYTEA` column
+/// * `ids` - IDs to send
+/// * `flush_bytes` - Approximate size of each flush; at least one tuple is sent per flush
+///
+/// # Returns
+/// * `BenchmarkResult<()>` - Success or database error; the caller still calls `finish`
+pub async fn send_ids_streamed<C>(
+    handle: &mut PgCopyIn<C>,
+    ids: &[[u8; 32]],
+    flush_bytes: usize,
+) -> BenchmarkResult<()>
+where
+    C: DerefMut<Target = PgConnection>,
+{
+    for flush in id_flushes(ids, flush_bytes) {
+        handle.send(flush).await?;
+    }
+    Ok(())
+}
+
+/// The flushes [`send_ids_streamed`] sends: the header with the first, the
+/// end-of-data marker with the last, and at most `flush_bytes` of tuples in each
+fn id_flushes(ids: &[[u8; 32]], flush_bytes: usize) -> impl Iterator<Item = Vec<u8>> + '_ {
+    let tuples_per_flush = (flush_bytes / ID_TUPLE_SIZE).max(1);
+    let mut chunks = ids.chunks(tuples_per_flush).peekable();
+    let mut writer = Some(BinaryCopyWriter::with_capacity(
+        1,
+        tuples_per_flush,
+        ID_TUPLE_SIZE,
+    ));
+    std::iter::from_fn(move || {
+        let mut current = writer.take()?;
+        for id in chunks.next().unwrap_or_default() {
+            current.write_tuple(&[id]);
+        }
+        if chunks.peek().is_none() {
+            return Some(current.finish());
+        }
+        let flush = current.take();
+        writer = Some(current);
+        Some(flush)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn one_column_tuples_are_count_length_and_payload() {
+        let id = [7u8; 32];
+        let mut writer = BinaryCopyWriter::with_capacity(1, 2, ID_TUPLE_SIZE);
+        writer.write_tuple(&[&id]);
+        writer.write_tuple(&[&id]);
+        let payload = writer.finish();
+
+        assert_eq!(
+            payload.len(),
+            BINARY_COPY_HEADER.len() + 2 * ID_TUPLE_SIZE + BINARY_COPY_TRAILER.len()
+        );
+        assert_eq!(&payload[..BINARY_COPY_HEADER.len()], &BINARY_COPY_HEADER);
+        let tuple = &payload[BINARY_COPY_HEADER.len()..][..ID_TUPLE_SIZE];
+        assert_eq!(&tuple[..2], &1i16.to_be_bytes());
+        assert_eq!(&tuple[2..6], &32u32.to_be_bytes());
+        assert_eq!(&tuple[6..], &id);
+        assert_eq!(&payload[payload.len() - 2..], &BINARY_COPY_TRAILER);
+    }
+
+    #[test]
+    fn two_column_tuples_prefix_each_field() {
+        let id = [1u8; 32];
+        let tenant: i32 = 42;
+        let tuple_size = BinaryCopyWriter::tuple_size(&[32, 4]);
+        let mut writer = BinaryCopyWriter::with_capacity(2, 1, tuple_size);
+        writer.write_tuple(&[&id, &tenant]);
+        let payload = writer.finish();
+
+        let tuple = &payload[BINARY_COPY_HEADER.len()..payload.len() - BINARY_COPY_TRAILER.len()];
+        assert_eq!(tuple.len(), tuple_size);
+        assert_eq!(&tuple[..2], &2i16.to_be_bytes());
+        assert_eq!(&tuple[2..6], &32u32.to_be_bytes());
+        assert_eq!(&tuple[6..38], &id);
+        assert_eq!(&tuple[38..42], &4u32.to_be_bytes());
+        assert_eq!(&tuple[42..], &42i32.to_be_bytes());
+    }
+
+    #[test]
+    fn streamed_flushes_add_up_to_the_whole_payload() {
+        let ids = crate::utils::generate_test_ids(10, crate::ID_RANGE);
+        let mut writer = BinaryCopyWriter::new(1);
+        for id in &ids {
+            writer.write_tuple(&[id]);
+        }
+        let whole = writer.finish();
+
+        // 10 IDs, 3 per flush: 3 + 3 + 3 + 1
+        let flushes: Vec<Vec<u8>> = id_flushes(&ids, 3 * ID_TUPLE_SIZE).collect();
+        assert_eq!(flushes.len(), 4);
+        assert_eq!(flushes[1].len(), 3 * ID_TUPLE_SIZE);
+        assert!(flushes.iter().all(|flush| {
+            flush.len() <= BINARY_COPY_HEADER.len() + 3 * ID_TUPLE_SIZE + BINARY_COPY_TRAILER.len()
+        }));
+        assert_eq!(flushes.concat(), whole);
+
+        // No IDs is a single flush of the header and trailer
+        let empty: Vec<Vec<u8>> = id_flushes(&[], 3 * ID_TUPLE_SIZE).collect();
+        assert_eq!(empty, [BinaryCopyWriter::new(1).finish()]);
+    }
+
+    /// Needs a database: `DATABASE_URL=postgres://... cargo test -- --ignored`
+    #[tokio::test]
+    #[ignore]
+    async fn streamed_copy_loads_every_id_across_many_flushes() {
+        use sqlx::postgres::PgPoolOptions;
+
+        let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        let pool = PgPoolOptions::new().connect(&url).await.unwrap();
+        let ids = crate::utils::generate_test_ids(250_000, crate::ID_RANGE);
+        let flush_bytes = 64 * 1024;
+        assert!(ids.len() * ID_TUPLE_SIZE > 100 * flush_bytes);
+
+        let mut transaction = pool.begin().await.unwrap();
+        sqlx::query("CREATE TEMP TABLE streamed_ids (id BYTEA) ON COMMIT DROP")
+            .execute(&mut *transaction)
+            .await
+            .unwrap();
+        let mut handle = transaction
+            .copy_in_raw("COPY streamed_ids (id) FROM STDIN WITH (FORMAT BINARY)")
+            .await
+            .unwrap();
+        send_ids_streamed(&mut handle, &ids, flush_bytes)
+            .await
+            .unwrap();
+        assert_eq!(handle.finish().await.unwrap(), ids.len() as u64);
+
+        let (rows, distinct): (i64, i64) =
+            sqlx::query_as("SELECT count(*), count(DISTINCT id) FROM streamed_ids")
+                .fetch_one(&mut *transaction)
+                .await
+                .unwrap();
+        assert_eq!((rows, distinct), (ids.len() as i64, ids.len() as i64));
+        transaction.rollback().await.unwrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "tuple field count must match")]
+    fn tuple_with_the_wrong_field_count_panics() {
+        let mut writer = BinaryCopyWriter::new(2);
+        writer.write_tuple(&[&[0u8; 32]]);
+    }
+}
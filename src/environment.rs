@@ -0,0 +1,95 @@
+//! Server and client environment of a run
+//!
+//! The same benchmark can rank differently on another PostgreSQL version, with
+//! other memory settings or on a machine with fewer cores. Capturing these once
+//! per run and writing them next to the results keeps every set of numbers tied
+//! to the environment that produced it.
+
+use crate::BenchmarkResult;
+use serde::Serialize;
+use sqlx::postgres::PgPool;
+
+/// Server settings captured by [`capture_environment`], in the order they are reported
+const SETTINGS: [&str; 3] = [
+    "shared_buffers",
+    "work_mem",
+    "max_parallel_workers_per_gather",
+];
+
+/// Where a run's numbers came from
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Environment {
+    /// Database the server version and settings were read from, e.g. `localhost:5432/postgres`
+    pub target: String,
+    /// `server_version`, e.g. `16.2 (Debian 16.2-1.pgdg120+2)`
+    pub server_version: String,
+    /// Server defaults of [`SETTINGS`] as `SHOW` prints them, e.g. `128MB`; a
+    /// `SET LOCAL` override such as `--work-mem` is not reflected
+    pub settings: Vec<(String, String)>,
+    /// CPUs available to the client process
+    pub client_cpus: usize,
+    /// Version of this crate
+    pub crate_version: String,
+}
+
+/// Capture the server version and settings, and the client's CPU count and version
+///
+/// # Arguments
+/// * `pool` - Pool connected to the benchmarked server
+/// * `target` - Label of that server, see [`crate::utils::target_label`]
+///
+/// # Returns
+/// * `BenchmarkResult<Environment>` - The environment, or a database error
+pub async fn capture_environment(pool: &PgPool, target: &str) -> BenchmarkResult<Environment> {
+    let server_version: String = sqlx::query_scalar("SELECT current_setting('server_version');")
+        .fetch_one(pool)
+        .await?;
+
+    let mut settings = Vec::with_capacity(SETTINGS.len());
+    for name in SETTINGS {
+        // `SHOW` formats memory settings with units, unlike `pg_settings.setting`
+        let value: String = sqlx::query_scalar(&format!("SHOW {};", name))
+            .fetch_one(pool)
+            .await?;
+        settings.push((name.to_string(), value));
+    }
+
+    Ok(Environment {
+        target: target.to_string(),
+        server_version,
+        settings,
+        client_cpus: std::thread::available_parallelism().map_or(1, |n| n.get()),
+        crate_version: env!("CARGO_PKG_VERSION").to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::postgres::PgPoolOptions;
+
+    /// Needs a database: `DATABASE_URL=postgres://... cargo test -- --ignored`
+    #[tokio::test]
+    #[ignore]
+    async fn captures_the_server_version_and_every_setting() {
+        let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        let pool = PgPoolOptions::new().connect(&url).await.unwrap();
+
+        let environment = capture_environment(&pool, "test").await.unwrap();
+        assert_eq!(environment.target, "test");
+        assert!(!environment.server_version.is_empty());
+        let names: Vec<&str> = environment
+            .settings
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect();
+        assert_eq!(names, SETTINGS);
+        assert!(
+            environment
+                .settings
+                .iter()
+                .all(|(_, value)| !value.is_empty())
+        );
+        assert!(environment.client_cpus >= 1);
+    }
+}
@@ -0,0 +1,1682 @@
+//! Seam between the suite's iteration loop and the database
+//!
+//! Per iteration the suite needs an ID set, a cache reset, the benchmark run and,
+//! with `--server-timing`, the server's share of it. Routing all of them through
+//! [`QueryExecutor`] lets the loop ([`IterationLoop`]: warmups, timing, validation,
+//! retries and failure handling) be driven by a test double that returns canned rows
+//! and controlled delays instead of a live PostgreSQL. [`BenchmarkContext`] is the
+//! real implementation.
+
+use crate::explain::{ExplainOptions, ServerTiming, server_timing};
+use crate::ordering::OrderingSample;
+use crate::utils::{
+    generate_ids_from_table_with_rng, generate_test_ids,
+    generate_test_ids_with_duplicates_with_rng, generate_test_ids_with_rng,
+};
+use crate::warnings::{Warning, WarningCategory};
+use crate::{
+    AdaptiveIterations, BenchmarkContext, BenchmarkError, BenchmarkIds, BenchmarkResult,
+    BenchmarkStats, BenchmarkTest, DISCONNECT_THRESHOLD, ExampleData, ID_RANGE, IdSet,
+    RETRY_ATTEMPT_FACTOR, RetryConfig,
+};
+use async_trait::async_trait;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+use std::fmt;
+use std::future::Future;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+use tokio::sync::Semaphore;
+use tokio::sync::mpsc::Sender;
+use tokio::task::JoinSet;
+use tracing::{error, info, warn};
+
+/// How each iteration's ID set is generated
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum IdSource {
+    /// Distinct hashes of random numbers, most of which match no row
+    #[default]
+    Hashed,
+    /// Hashes of random numbers, which may repeat (`--allow-duplicates`)
+    HashedWithDuplicates,
+    /// Sampled from the lookup table so that `hit_rate` of them match (`--hit-rate`)
+    Table { hit_rate: f64 },
+}
+
+/// Executes benchmark iterations on behalf of the suite
+#[async_trait]
+pub trait QueryExecutor: Send + Sync {
+    /// Reset server-side caches before an iteration
+    ///
+    /// # Returns
+    /// * `Result<(), BenchmarkError>` - Success or database error
+    async fn reset(&self) -> BenchmarkResult<()>;
+
+    /// Execute one iteration of the benchmark with the given IDs
+    ///
+    /// # Arguments
+    /// * `benchmark` - Benchmark to execute
+    /// * `ids` - IDs to query with, in the benchmark's representation
+    ///
+    /// # Returns
+    /// * `Result<Vec<ExampleData>, BenchmarkError>` - Query results or error
+    async fn execute(
+        &self,
+        benchmark: &dyn BenchmarkTest,
+        ids: BenchmarkIds<'_>,
+    ) -> BenchmarkResult<Vec<ExampleData>>;
+
+    /// Time spent waiting for connections since the last call, resetting it
+    ///
+    /// # Returns
+    /// * `Option<Duration>` - The wait, or `None` if it isn't measured
+    fn take_acquire_time(&self) -> Option<Duration> {
+        None // Default: acquire time is part of the run duration
+    }
+
+    /// Generate one iteration's ID set
+    ///
+    /// # Arguments
+    /// * `rng` - The run's seeded ID generator
+    /// * `num_ids` - Size of the ID set
+    /// * `source` - How to generate the IDs
+    ///
+    /// # Returns
+    /// * `BenchmarkResult<Vec<[u8; 32]>>` - The IDs, or the error sampling the table
+    async fn generate_ids(
+        &self,
+        rng: &mut StdRng,
+        num_ids: usize,
+        source: IdSource,
+    ) -> BenchmarkResult<Vec<[u8; 32]>> {
+        match source {
+            IdSource::Hashed => Ok(generate_test_ids_with_rng(rng, num_ids, ID_RANGE)),
+            IdSource::HashedWithDuplicates => Ok(generate_test_ids_with_duplicates_with_rng(
+                rng, num_ids, ID_RANGE,
+            )),
+            IdSource::Table { .. } => Err(BenchmarkError::Setup {
+                message: "no table to sample IDs from".to_string(),
+            }),
+        }
+    }
+
+    /// Reset caches, then measure one ID set's server planning and execution time
+    ///
+    /// # Arguments
+    /// * `benchmark` - Benchmark whose final statement to measure
+    /// * `ids` - ID set of the iteration just timed
+    /// * `run_number` - Run the timing belongs to
+    ///
+    /// # Returns
+    /// * `BenchmarkResult<Option<ServerTiming>>` - Timing, or `None` if it can't be measured
+    async fn server_timing(
+        &self,
+        _benchmark: &dyn BenchmarkTest,
+        _ids: BenchmarkIds<'_>,
+        _run_number: usize,
+    ) -> BenchmarkResult<Option<ServerTiming>> {
+        Ok(None) // Default: no server to ask
+    }
+}
+
+#[async_trait]
+impl QueryExecutor for BenchmarkContext {
+    async fn reset(&self) -> BenchmarkResult<()> {
+        self.clear_caches().await
+    }
+
+    async fn execute(
+        &self,
+        benchmark: &dyn BenchmarkTest,
+        ids: BenchmarkIds<'_>,
+    ) -> BenchmarkResult<Vec<ExampleData>> {
+        self.run_with_timeout(benchmark, ids).await
+    }
+
+    fn take_acquire_time(&self) -> Option<Duration> {
+        BenchmarkContext::take_acquire_time(self)
+    }
+
+    async fn generate_ids(
+        &self,
+        rng: &mut StdRng,
+        num_ids: usize,
+        source: IdSource,
+    ) -> BenchmarkResult<Vec<[u8; 32]>> {
+        match source {
+            IdSource::Table { hit_rate } => {
+                generate_ids_from_table_with_rng(rng, &self.pool, &self.schema, num_ids, hit_rate)
+                    .await
+            }
+            IdSource::Hashed => Ok(generate_test_ids_with_rng(rng, num_ids, ID_RANGE)),
+            IdSource::HashedWithDuplicates => Ok(generate_test_ids_with_duplicates_with_rng(
+                rng, num_ids, ID_RANGE,
+            )),
+        }
+    }
+
+    async fn server_timing(
+        &self,
+        benchmark: &dyn BenchmarkTest,
+        ids: BenchmarkIds<'_>,
+        run_number: usize,
+    ) -> BenchmarkResult<Option<ServerTiming>> {
+        self.clear_caches().await?;
+        let Some(plan) = benchmark
+            .explain(self, ids, &ExplainOptions::analyze_json())
+            .await?
+        else {
+            return Ok(None);
+        };
+        server_timing(&plan, run_number).map(Some)
+    }
+}
+
+/// Outcome of a single timed iteration
+pub struct Iteration {
+    /// Wall-clock time the timed section started
+    pub wall_start: SystemTime,
+    /// Monotonic time the timed section started
+    pub start: Instant,
+    /// Duration of the timed section
+    pub duration: Duration,
+    /// Rows returned, or the error that failed the iteration
+    pub result: BenchmarkResult<Vec<ExampleData>>,
+    /// Error from resetting caches beforehand, if the reset failed
+    pub reset_error: Option<BenchmarkError>,
+    /// Client heap growth during the timed section, if the `mem-profiling` feature is on
+    pub peak_bytes: Option<usize>,
+    /// Times the iteration was rerun after a transient error
+    pub retries: usize,
+    /// Part of `duration` spent waiting for connections, if measured
+    pub acquire: Option<Duration>,
+}
+
+/// A timed iteration that failed, kept for the report and `failures.json`
+#[derive(Debug)]
+pub struct FailedRun {
+    /// Reported benchmark name
+    pub benchmark: String,
+    /// Attempt that failed, from 1, counting earlier failed attempts
+    pub iteration: usize,
+    pub error: BenchmarkError,
+}
+
+impl FailedRun {
+    /// Record a failed iteration
+    ///
+    /// # Arguments
+    /// * `benchmark` - Reported benchmark name
+    /// * `iteration` - Attempt that failed, from 1
+    /// * `error` - Why it failed
+    pub fn new(benchmark: impl Into<String>, iteration: usize, error: BenchmarkError) -> Self {
+        Self {
+            benchmark: benchmark.into(),
+            iteration,
+            error,
+        }
+    }
+}
+
+impl fmt::Display for FailedRun {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} iteration {}: {}",
+            self.benchmark, self.iteration, self.error
+        )
+    }
+}
+
+impl Serialize for FailedRun {
+    /// The error as its [`kind`](BenchmarkError::kind) and message, since it can't be
+    /// serialized itself
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("FailedRun", 4)?;
+        state.serialize_field("benchmark", &self.benchmark)?;
+        state.serialize_field("iteration", &self.iteration)?;
+        state.serialize_field("kind", self.error.kind())?;
+        state.serialize_field("error", &self.error.to_string())?;
+        state.end()
+    }
+}
+
+/// Write failed runs as a pretty-printed JSON array, replacing the file
+///
+/// # Arguments
+/// * `failures` - Failed runs, in the order they happened
+/// * `json_path` - Path to the JSON file to write to
+///
+/// # Returns
+/// * `BenchmarkResult<()>` - Success or IO/serialization error
+pub fn export_failures_to_json(failures: &[FailedRun], json_path: &Path) -> BenchmarkResult<()> {
+    crate::utils::write_atomically(json_path, false, |file, _| {
+        serde_json::to_writer_pretty(&mut *file, failures)?;
+        Ok(())
+    })
+}
+
+/// Progress of a benchmark after one of its iterations, for live reporting
+///
+/// Sent over a `tokio::sync::mpsc` channel after every iteration that ran, failed
+/// or not, so a progress bar doesn't have to parse the logs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProgressEvent {
+    /// Reported benchmark name
+    pub benchmark: String,
+    /// Iterations attempted so far, including failed ones
+    pub iteration: usize,
+    /// Iterations the benchmark is meant to run
+    pub total: usize,
+    /// Duration of the iteration just run
+    pub last_duration: Duration,
+}
+
+/// Run the benchmark `count` times untimed, each with a fresh random ID set
+///
+/// Caches are not reset, so later warmups and the first timed iteration's plan
+/// lookups can benefit from the earlier ones.
+///
+/// # Arguments
+/// * `executor` - Executor to run against
+/// * `benchmark` - Benchmark to warm up
+/// * `num_ids` - Size of each warmup's ID set
+/// * `count` - Number of warmup runs
+///
+/// # Returns
+/// * `BenchmarkResult<()>` - Success, or the first warmup's error
+pub async fn run_warmups(
+    executor: &dyn QueryExecutor,
+    benchmark: &dyn BenchmarkTest,
+    num_ids: usize,
+    count: usize,
+) -> BenchmarkResult<()> {
+    for warmup in 1..=count {
+        info!("Warmup {}/{} for {}", warmup, count, benchmark.name());
+        let ids = IdSet::new(generate_test_ids(num_ids, ID_RANGE));
+        executor
+            .execute(benchmark, ids.get(benchmark.id_kind()))
+            .await?;
+    }
+    Ok(())
+}
+
+/// Results, warnings and failures collected while a suite's benchmarks run
+///
+/// Shared by the benchmarks of one `run_all`, which may run concurrently.
+#[derive(Default)]
+pub struct RunOutput {
+    pub results: Mutex<Vec<BenchmarkStats>>,
+    pub warnings: Mutex<Vec<Warning>>,
+    pub failures: Mutex<Vec<FailedRun>>,
+    /// Connection-level failures since the last query that reached the server
+    pub connection_failures: AtomicUsize,
+}
+
+impl RunOutput {
+    /// Record a problem to surface at the top of the report
+    pub fn warn(&self, warning: Warning) {
+        self.warnings
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(warning);
+    }
+
+    /// Record a failed iteration for the report and `failures.json`
+    pub fn fail(&self, failure: FailedRun) {
+        self.failures
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(failure);
+    }
+
+    /// Record a finished benchmark's statistics
+    pub fn finish(&self, stats: BenchmarkStats) {
+        self.results
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(stats);
+    }
+
+    /// Note that a query reached the server, resetting the connection failure count
+    pub fn reached_server(&self) {
+        self.connection_failures.store(0, Ordering::Relaxed);
+    }
+
+    /// Track consecutive connection-level failures across iterations and benchmarks
+    ///
+    /// A failure that reached the server (a bad query, a validation error) resets
+    /// the count, since the database is evidently still there.
+    ///
+    /// # Arguments
+    /// * `error` - Error of the failed run
+    /// * `abort_on_disconnect` - Whether `--abort-on-disconnect` is set
+    ///
+    /// # Returns
+    /// * `BenchmarkResult<()>` - `Err(Disconnected)` once `abort_on_disconnect` is set
+    ///   and the count reaches [`DISCONNECT_THRESHOLD`]
+    pub fn record_failure(
+        &self,
+        error: &BenchmarkError,
+        abort_on_disconnect: bool,
+    ) -> BenchmarkResult<()> {
+        if !error.is_connection_error() {
+            self.reached_server();
+            return Ok(());
+        }
+
+        let failures = self.connection_failures.fetch_add(1, Ordering::Relaxed) + 1;
+        if abort_on_disconnect && failures >= DISCONNECT_THRESHOLD {
+            return Err(BenchmarkError::Disconnected {
+                failures,
+                message: error.to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Take the collected results, warnings and failures
+    ///
+    /// # Returns
+    /// * `(Vec<BenchmarkStats>, Vec<Warning>, Vec<FailedRun>)` - Each in the order recorded
+    pub fn into_parts(self) -> (Vec<BenchmarkStats>, Vec<Warning>, Vec<FailedRun>) {
+        (
+            self.results.into_inner().unwrap_or_else(|e| e.into_inner()),
+            self.warnings
+                .into_inner()
+                .unwrap_or_else(|e| e.into_inner()),
+            self.failures
+                .into_inner()
+                .unwrap_or_else(|e| e.into_inner()),
+        )
+    }
+}
+
+/// Count of a failure repeated across iterations, keeping the first error
+#[derive(Default)]
+struct FailureTally {
+    count: usize,
+    first: Option<String>,
+}
+
+impl FailureTally {
+    /// Record one failure
+    fn record(&mut self, error: &BenchmarkError) {
+        self.count += 1;
+        self.first.get_or_insert_with(|| error.to_string());
+    }
+
+    /// Describe the failures, e.g. `3 of 100 iterations failed (first: ...)`
+    ///
+    /// # Arguments
+    /// * `iterations` - Iterations attempted
+    /// * `outcome` - What happened to the affected iterations
+    ///
+    /// # Returns
+    /// * `Option<String>` - `None` if nothing failed
+    fn summary(&self, iterations: usize, outcome: &str) -> Option<String> {
+        let first = self.first.as_ref()?;
+        Some(format!(
+            "{} of {} iterations {} (first: {})",
+            self.count, iterations, outcome, first
+        ))
+    }
+}
+
+/// A successful timed iteration, for the reports built after the loop
+#[derive(Debug, Clone)]
+pub struct TimedRun {
+    /// Attempt the run came from, from 1, counting failed attempts
+    pub attempt: usize,
+    /// Wall-clock time the timed section started
+    pub wall_start: SystemTime,
+    /// Monotonic time the timed section started
+    pub start: Instant,
+    /// Per-execution duration of the iteration
+    pub duration: Duration,
+    /// Rows returned by one execution
+    pub rows: usize,
+}
+
+/// What a benchmark's iteration loop did, beyond the runs added to its statistics
+#[derive(Debug, Default)]
+pub struct LoopOutcome {
+    /// Successful iterations, in order
+    pub runs: Vec<TimedRun>,
+    /// IDs of the first iteration, kept when it sampled the row order
+    pub first_ids: Option<IdSet>,
+    /// Iterations that failed, ID generation included
+    pub failures: usize,
+    /// Whether a Ctrl-C stopped the loop early
+    pub interrupted: bool,
+}
+
+/// Settings of a benchmark's warmups and timed iteration loop
+///
+/// [`run`](Self::run) holds everything the suite does per iteration apart from the
+/// database itself, which it reaches through a [`QueryExecutor`].
+#[derive(Debug, Clone)]
+pub struct IterationLoop {
+    /// Successful runs to collect; replaced by `adaptive.max` when set
+    pub iterations: usize,
+    /// Size of each iteration's ID set
+    pub num_ids: usize,
+    /// How the ID sets are generated
+    pub id_source: IdSource,
+    /// Seed every benchmark replays its sequence of ID sets from
+    pub id_seed: u64,
+    /// Executions per iteration; see [`run_iteration`]
+    pub batch_size: usize,
+    /// Retries of transient failures within an iteration
+    pub retry: RetryConfig,
+    /// Overrides the benchmark's `warmup_iterations` when set
+    pub warmup: Option<usize>,
+    /// Stops once the durations are stable, instead of after a fixed count
+    pub adaptive: Option<AdaptiveIterations>,
+    /// Keep attempting until `iterations` runs succeed, up to [`RETRY_ATTEMPT_FACTOR`]
+    /// times as many attempts
+    pub retry_failed_to_target: bool,
+    /// Stop the suite after [`DISCONNECT_THRESHOLD`] consecutive connection failures
+    pub abort_on_disconnect: bool,
+    /// Measure each run's server planning and execution time
+    pub server_timing: bool,
+    /// Sample the first iteration's row order for `--compare-ordering`
+    pub compare_ordering: bool,
+    /// Record client heap growth; off when benchmarks run concurrently, as they would
+    /// count each other's allocations
+    pub record_peak_bytes: bool,
+    /// Set by the first Ctrl-C: no further iterations start
+    pub interrupted: Arc<AtomicBool>,
+    /// Receives a [`ProgressEvent`] after every iteration
+    pub progress: Option<Sender<ProgressEvent>>,
+}
+
+impl Default for IterationLoop {
+    fn default() -> Self {
+        Self {
+            iterations: 0,
+            num_ids: 0,
+            id_source: IdSource::default(),
+            id_seed: 0,
+            batch_size: 1,
+            retry: RetryConfig::default(),
+            warmup: None,
+            adaptive: None,
+            retry_failed_to_target: false,
+            abort_on_disconnect: false,
+            server_timing: false,
+            compare_ordering: false,
+            record_peak_bytes: true,
+            interrupted: Arc::new(AtomicBool::new(false)),
+            progress: None,
+        }
+    }
+}
+
+impl IterationLoop {
+    /// Successful runs to collect: the adaptive maximum, or `iterations`
+    pub fn target(&self) -> usize {
+        self.adaptive
+            .map_or(self.iterations, |adaptive| adaptive.max)
+    }
+
+    /// Attempts allowed, failed ones included
+    pub fn max_attempts(&self) -> usize {
+        if self.retry_failed_to_target {
+            self.target().saturating_mul(RETRY_ATTEMPT_FACTOR)
+        } else {
+            self.target()
+        }
+    }
+
+    /// Run the benchmark's warmups, if it or `warmup` asks for any
+    ///
+    /// A failed warmup is recorded as a warning on `stats_name`.
+    ///
+    /// # Arguments
+    /// * `executor` - Executor to run against
+    /// * `benchmark` - Benchmark to warm up
+    /// * `stats_name` - Reported name of the benchmark
+    /// * `output` - Where warnings and connection failures are recorded
+    ///
+    /// # Returns
+    /// * `BenchmarkResult<bool>` - Whether the benchmark should go on to its timed
+    ///   iterations, or `Err(Disconnected)` if `abort_on_disconnect` stops the suite
+    pub async fn warm_up(
+        &self,
+        executor: &dyn QueryExecutor,
+        benchmark: &dyn BenchmarkTest,
+        stats_name: &str,
+        output: &RunOutput,
+    ) -> BenchmarkResult<bool> {
+        let name = benchmark.name();
+        let warmups = self.warmup.unwrap_or_else(|| benchmark.warmup_iterations());
+        if warmups == 0 {
+            return Ok(true);
+        }
+
+        info!("Warming up benchmark: {} ({} runs)", name, warmups);
+        match run_warmups(executor, benchmark, self.num_ids, warmups).await {
+            Ok(()) => {
+                output.reached_server();
+                info!("Warmup completed for: {}", name);
+                Ok(true)
+            }
+            Err(e) => {
+                error!("Warmup failed for {}: {}", name, e);
+                output.warn(Warning::new(
+                    WarningCategory::Failures,
+                    stats_name,
+                    format!("warmup failed, benchmark skipped: {}", e),
+                ));
+                output.record_failure(&e, self.abort_on_disconnect)?;
+                Ok(false)
+            }
+        }
+    }
+
+    /// Run the timed iterations, adding each successful run to `stats`
+    ///
+    /// Each attempt generates its IDs before the timer starts, then runs
+    /// [`run_iteration`]. The first successful run's rows are checked with the
+    /// benchmark's `validate`. Failed attempts are recorded in `output` and, unless
+    /// `retry_failed_to_target` is set, count towards `iterations`. Failures, cache
+    /// resets that didn't happen and early stops are summarised as warnings.
+    ///
+    /// # Arguments
+    /// * `executor` - Executor to run against
+    /// * `benchmark` - Benchmark to run
+    /// * `stats` - Statistics the runs are added to, named for the report
+    /// * `output` - Where warnings, failed runs and connection failures are recorded
+    ///
+    /// # Returns
+    /// * `BenchmarkResult<LoopOutcome>` - What ran, or `Err(Disconnected)` if
+    ///   `abort_on_disconnect` stops the suite
+    pub async fn run(
+        &self,
+        executor: &dyn QueryExecutor,
+        benchmark: &dyn BenchmarkTest,
+        stats: &mut BenchmarkStats,
+        output: &RunOutput,
+    ) -> BenchmarkResult<LoopOutcome> {
+        let name = benchmark.name();
+        let iterations = self.target();
+        let max_attempts = self.max_attempts();
+        // Every benchmark replays the same sequence of ID sets from the run's seed
+        let mut id_rng = StdRng::seed_from_u64(self.id_seed);
+
+        let mut outcome = LoopOutcome::default();
+        let mut reset_failures = FailureTally::default();
+        let mut iteration_failures = FailureTally::default();
+        let mut server_timing_failures = FailureTally::default();
+        // Turned off if the benchmark has no single statement to EXPLAIN
+        let mut measure_server_timing = self.server_timing;
+        let mut validated = false;
+        let mut stable = false;
+        for i in 0..max_attempts {
+            if stats.runs.len() >= iterations {
+                break;
+            }
+            if self.interrupted.load(Ordering::Relaxed) {
+                info!(
+                    "Interrupted: stopping {} after {} of {} runs",
+                    name,
+                    stats.runs.len(),
+                    iterations
+                );
+                outcome.interrupted = true;
+                break;
+            }
+            if let Some(adaptive) = self.adaptive
+                && adaptive.is_done(stats)
+            {
+                info!(
+                    "{} is stable after {} runs (CV {:.3})",
+                    name,
+                    stats.runs.len(),
+                    stats.coefficient_of_variation()
+                );
+                stable = true;
+                break;
+            }
+            stats.attempts += 1;
+
+            // Generate this iteration's IDs before the timer starts
+            let ids = match executor
+                .generate_ids(&mut id_rng, self.num_ids, self.id_source)
+                .await
+            {
+                Ok(ids) => IdSet::new(ids),
+                Err(e) => {
+                    warn!(
+                        "Benchmark {} iteration {}/{}: generating IDs failed: {}",
+                        name,
+                        i + 1,
+                        max_attempts,
+                        e
+                    );
+                    output.record_failure(&e, self.abort_on_disconnect)?;
+                    iteration_failures.record(&e);
+                    output.fail(FailedRun::new(&stats.name, i + 1, e));
+                    continue;
+                }
+            };
+            stats.record_input(ids.hashes());
+            let benchmark_ids = ids.get(benchmark.id_kind());
+
+            // Clear caches (for cold query performance), then time the run
+            let iteration = run_iteration(
+                executor,
+                benchmark,
+                benchmark_ids,
+                self.batch_size,
+                self.retry,
+            )
+            .await;
+            if let Some(progress) = &self.progress {
+                // A closed receiver only means nobody is watching any more
+                let _ = progress
+                    .send(ProgressEvent {
+                        benchmark: stats.name.clone(),
+                        iteration: stats.attempts,
+                        total: iterations,
+                        last_duration: iteration.duration,
+                    })
+                    .await;
+            }
+            if let Some(e) = iteration.reset_error {
+                reset_failures.record(&e);
+            }
+            let duration = iteration.duration;
+            let results = match iteration.result {
+                Ok(results) => results,
+                Err(e) => {
+                    warn!(
+                        "Benchmark {} iteration {}/{} failed: {}",
+                        name,
+                        i + 1,
+                        max_attempts,
+                        e
+                    );
+                    output.record_failure(&e, self.abort_on_disconnect)?;
+                    iteration_failures.record(&e);
+                    output.fail(FailedRun::new(&stats.name, i + 1, e));
+                    continue;
+                }
+            };
+            output.reached_server();
+
+            // Check the benchmark's invariants once, outside the timed section
+            if !validated {
+                validated = true;
+                if let Err(e) = benchmark.validate(benchmark_ids, &results) {
+                    warn!("Validation failed for {}: {}", name, e);
+                    output.warn(Warning::new(
+                        WarningCategory::Rows,
+                        &stats.name,
+                        format!("validation failed: {}", e),
+                    ));
+                }
+            }
+
+            // Split the server's share of this ID set into planning and execution
+            if measure_server_timing {
+                let run_number = stats.runs.len() + 1;
+                match executor
+                    .server_timing(benchmark, benchmark_ids, run_number)
+                    .await
+                {
+                    Ok(Some(timing)) => stats.server_timings.push(timing),
+                    Ok(None) => {
+                        info!(
+                            "{} has no single statement to EXPLAIN; no server timing",
+                            name
+                        );
+                        measure_server_timing = false;
+                    }
+                    Err(e) => {
+                        warn!("EXPLAIN ANALYZE failed for {}: {}", name, e);
+                        server_timing_failures.record(&e);
+                    }
+                }
+            }
+
+            // Only the first ID set is shared with the other strategies' first iteration
+            if self.compare_ordering && i == 0 && benchmark.returns_responses() {
+                stats.ordering = Some(OrderingSample::new(&results));
+                outcome.first_ids = Some(ids);
+            }
+
+            stats.add_result(duration, results.len());
+            stats.record_bytes(&results);
+            stats.acquire_times.extend(iteration.acquire);
+            if let Some(peak_bytes) = iteration.peak_bytes
+                && self.record_peak_bytes
+            {
+                stats.record_peak_bytes(peak_bytes);
+            }
+            outcome.runs.push(TimedRun {
+                attempt: i + 1,
+                wall_start: iteration.wall_start,
+                start: iteration.start,
+                duration,
+                rows: results.len(),
+            });
+            info!(
+                "Benchmark {} iteration {}/{} completed in {:?} ({} rows)",
+                name,
+                i + 1,
+                max_attempts,
+                duration,
+                results.len()
+            );
+        }
+        outcome.failures = iteration_failures.count;
+
+        if let Some(summary) = reset_failures.summary(
+            stats.attempts,
+            "ran without clearing caches and may be warm",
+        ) {
+            output.warn(Warning::new(
+                WarningCategory::CacheClear,
+                &stats.name,
+                summary,
+            ));
+        }
+        if let Some(summary) =
+            server_timing_failures.summary(stats.runs.len(), "have no server timing")
+        {
+            output.warn(Warning::new(WarningCategory::Plan, &stats.name, summary));
+        }
+        if let Some(summary) = iteration_failures.summary(stats.attempts, "failed") {
+            output.warn(Warning::new(
+                WarningCategory::Failures,
+                &stats.name,
+                summary,
+            ));
+        }
+        if outcome.interrupted {
+            output.warn(Warning::new(
+                WarningCategory::SampleSize,
+                &stats.name,
+                format!(
+                    "interrupted after {} of {} runs",
+                    stats.runs.len(),
+                    iterations
+                ),
+            ));
+        } else if self.retry_failed_to_target && !stable {
+            if stats.runs.len() < iterations {
+                warn!(
+                    "{} gave up after {} attempts with {} of {} successful runs",
+                    name,
+                    stats.attempts,
+                    stats.runs.len(),
+                    iterations
+                );
+                output.warn(Warning::new(
+                    WarningCategory::Failures,
+                    &stats.name,
+                    format!(
+                        "only {} of {} target runs succeeded within the {}-attempt cap",
+                        stats.runs.len(),
+                        iterations,
+                        max_attempts
+                    ),
+                ));
+            } else if stats.attempts > iterations {
+                info!(
+                    "{} needed {} attempts to reach {} successful runs",
+                    name, stats.attempts, iterations
+                );
+            }
+        }
+
+        Ok(outcome)
+    }
+}
+
+/// Spawn each future as its own task, at most `limit` running at a time
+///
+/// Each task holds a semaphore permit while it runs, so on a multi-threaded runtime
+/// the benchmarks' client-side work (COPY encoding, row decoding) runs on separate
+/// workers instead of inside each other's timed sections. The futures must own
+/// their state, e.g. behind an `Arc`. A panicking task panics the caller.
+///
+/// # Arguments
+/// * `limit` - Maximum number of futures in progress at once; at least 1
+/// * `futures` - Futures to run; each waits for a permit before it starts
+///
+/// # Returns
+/// * `Vec<F::Output>` - Each future's output, in the order given
+pub async fn run_bounded<F>(limit: usize, futures: impl IntoIterator<Item = F>) -> Vec<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    let semaphore = Arc::new(Semaphore::new(limit.max(1)));
+    let mut tasks = JoinSet::new();
+    for (index, future) in futures.into_iter().enumerate() {
+        let semaphore = semaphore.clone();
+        tasks.spawn(async move {
+            let _permit = semaphore
+                .acquire_owned()
+                .await
+                .expect("semaphore is never closed");
+            (index, future.await)
+        });
+    }
+
+    let mut outputs: Vec<Option<F::Output>> = (0..tasks.len()).map(|_| None).collect();
+    while let Some(joined) = tasks.join_next().await {
+        match joined {
+            Ok((index, output)) => outputs[index] = Some(output),
+            Err(e) => std::panic::resume_unwind(e.into_panic()),
+        }
+    }
+    outputs.into_iter().map(Option::unwrap).collect()
+}
+
+/// Reset caches, then time `batch_size` executions of the benchmark
+///
+/// A failed cache reset is logged, returned in [`Iteration::reset_error`] and the
+/// iteration still runs; only the executions themselves are timed. Caches are reset
+/// once per batch, so every execution after the first runs warm against the same IDs.
+///
+/// An iteration that fails with a transient error (see [`BenchmarkError::is_transient`])
+/// is rerun from the cache reset, after a backoff that isn't timed, until it succeeds
+/// or `retry.max_attempts` is used up.
+///
+/// # Arguments
+/// * `executor` - Executor to run against
+/// * `benchmark` - Benchmark to execute
+/// * `ids` - IDs to query with, generated before the call
+/// * `batch_size` - Executions per iteration; must be at least 1
+/// * `retry` - Retries of transient failures
+///
+/// # Returns
+/// * `Iteration` - Per-execution duration (batch total / `batch_size`) and the
+///   rows of a single execution, or the first error of the last attempt
+pub async fn run_iteration(
+    executor: &dyn QueryExecutor,
+    benchmark: &dyn BenchmarkTest,
+    ids: BenchmarkIds<'_>,
+    batch_size: usize,
+    retry: RetryConfig,
+) -> Iteration {
+    let mut retries = 0;
+    loop {
+        let iteration = run_iteration_once(executor, benchmark, ids, batch_size).await;
+        match &iteration.result {
+            Err(e) if e.is_transient() && retries + 1 < retry.max_attempts => {
+                retries += 1;
+                let delay = retry.delay(retries);
+                warn!(
+                    "Transient failure in {}, retry {}/{} in {:?}: {}",
+                    benchmark.name(),
+                    retries,
+                    retry.max_attempts - 1,
+                    delay,
+                    e
+                );
+                tokio::time::sleep(delay).await;
+            }
+            _ => {
+                return Iteration {
+                    retries,
+                    ..iteration
+                };
+            }
+        }
+    }
+}
+
+/// A single attempt of [`run_iteration`]
+async fn run_iteration_once(
+    executor: &dyn QueryExecutor,
+    benchmark: &dyn BenchmarkTest,
+    ids: BenchmarkIds<'_>,
+    batch_size: usize,
+) -> Iteration {
+    let reset_error = executor.reset().await.err();
+    if let Some(e) = &reset_error {
+        warn!("Failed to clear caches for {}: {}", benchmark.name(), e);
+    }
+
+    #[cfg(feature = "mem-profiling")]
+    let allocations = crate::memory::AllocationScope::start();
+    // Only the timed section's waits count, not the reset's
+    executor.take_acquire_time();
+    let wall_start = SystemTime::now();
+    // Tokio's clock, which is the system's monotonic clock unless a test pauses it
+    let start = tokio::time::Instant::now();
+    let mut result = executor.execute(benchmark, ids).await;
+    for _ in 1..batch_size {
+        if result.is_err() {
+            break;
+        }
+        result = executor.execute(benchmark, ids).await;
+    }
+    let duration = start.elapsed() / batch_size.max(1) as u32;
+    let acquire = executor
+        .take_acquire_time()
+        .map(|acquire| acquire / batch_size.max(1) as u32);
+    #[cfg(feature = "mem-profiling")]
+    let peak_bytes = Some(allocations.finish());
+    #[cfg(not(feature = "mem-profiling"))]
+    let peak_bytes = None;
+
+    Iteration {
+        wall_start,
+        start: start.into_std(),
+        duration,
+        result,
+        reset_error,
+        peak_bytes,
+        retries: 0,
+        acquire,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::benchmarks::{AnyArrayBenchmark, UnnestArrayBenchmark};
+    use crate::keys::bigint_key_for;
+    use crate::{BenchmarkStats, IdKind};
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+    /// Executor that counts calls instead of touching a database
+    ///
+    /// Each execution takes `delay` and returns a single row.
+    #[derive(Default)]
+    struct CountingExecutor {
+        resets: AtomicUsize,
+        executions: AtomicUsize,
+        delay: Duration,
+    }
+
+    #[async_trait]
+    impl QueryExecutor for CountingExecutor {
+        async fn reset(&self) -> BenchmarkResult<()> {
+            self.resets.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+
+        async fn execute(
+            &self,
+            _benchmark: &dyn BenchmarkTest,
+            _ids: BenchmarkIds<'_>,
+        ) -> BenchmarkResult<Vec<ExampleData>> {
+            self.executions.fetch_add(1, Ordering::SeqCst);
+            if !self.delay.is_zero() {
+                tokio::time::sleep(self.delay).await;
+            }
+            Ok(vec![ExampleData {
+                response: "row".to_string(),
+                hash: None,
+            }])
+        }
+    }
+
+    struct ThreeWarmups;
+
+    #[async_trait]
+    impl BenchmarkTest for ThreeWarmups {
+        async fn run(
+            &self,
+            _context: &BenchmarkContext,
+            _ids: BenchmarkIds<'_>,
+        ) -> BenchmarkResult<Vec<ExampleData>> {
+            Ok(Vec::new())
+        }
+
+        fn name(&self) -> &'static str {
+            "three_warmups"
+        }
+
+        fn description(&self) -> &'static str {
+            "Warms up three times"
+        }
+
+        fn warmup_iterations(&self) -> usize {
+            3
+        }
+    }
+
+    #[tokio::test]
+    async fn warmups_run_untimed_before_the_timed_iterations() {
+        let executor = CountingExecutor::default();
+        let benchmark = ThreeWarmups;
+        assert!(benchmark.needs_warmup());
+
+        run_warmups(&executor, &benchmark, 10, benchmark.warmup_iterations())
+            .await
+            .unwrap();
+        assert_eq!(executor.executions.load(Ordering::SeqCst), 3);
+        assert_eq!(executor.resets.load(Ordering::SeqCst), 0);
+
+        for _ in 0..2 {
+            let iteration = run_iteration(
+                &executor,
+                &benchmark,
+                BenchmarkIds::Bytes(&[[0u8; 32]]),
+                1,
+                RetryConfig::default(),
+            )
+            .await;
+            assert!(iteration.result.is_ok());
+        }
+        assert_eq!(executor.executions.load(Ordering::SeqCst), 5);
+        assert_eq!(executor.resets.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn a_batch_reports_the_per_execution_duration_and_rows() {
+        let executor = CountingExecutor {
+            delay: Duration::from_millis(10),
+            ..CountingExecutor::default()
+        };
+        let iteration = run_iteration(
+            &executor,
+            &ThreeWarmups,
+            BenchmarkIds::Bytes(&[[0u8; 32]]),
+            4,
+            RetryConfig::default(),
+        )
+        .await;
+
+        // Four executions against one cache reset, reported as one execution's worth
+        assert_eq!(executor.executions.load(Ordering::SeqCst), 4);
+        assert_eq!(executor.resets.load(Ordering::SeqCst), 1);
+        assert_eq!(iteration.result.unwrap().len(), 1);
+        // The paused clock only advances by the sleeps: 40ms in total, 10ms each
+        assert_eq!(iteration.duration, Duration::from_millis(40) / 4);
+    }
+
+    #[tokio::test]
+    async fn bounded_runs_complete_every_benchmark_within_the_limit() {
+        let executor = Arc::new(CountingExecutor::default());
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let names = ["a", "b", "c", "d", "e"];
+
+        let results = run_bounded(
+            2,
+            names.into_iter().map(|name| {
+                let (executor, in_flight, peak) =
+                    (executor.clone(), in_flight.clone(), peak.clone());
+                async move {
+                    let running = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                    peak.fetch_max(running, Ordering::SeqCst);
+                    let mut stats = BenchmarkStats::new(name.to_string(), String::new(), 1);
+                    for _ in 0..3 {
+                        let iteration = run_iteration(
+                            executor.as_ref(),
+                            &ThreeWarmups,
+                            BenchmarkIds::Bytes(&[[0u8; 32]]),
+                            1,
+                            RetryConfig::default(),
+                        )
+                        .await;
+                        stats.add_result(iteration.duration, iteration.result.unwrap().len());
+                        // Let the other runs make progress, as a query's await would
+                        tokio::task::yield_now().await;
+                    }
+                    in_flight.fetch_sub(1, Ordering::SeqCst);
+                    stats
+                }
+            }),
+        )
+        .await;
+
+        let finished: Vec<&str> = results.iter().map(|stats| stats.name.as_str()).collect();
+        assert_eq!(finished, names);
+        assert!(results.iter().all(|stats| stats.runs.len() == 3));
+        assert_eq!(executor.executions.load(Ordering::SeqCst), 15);
+        assert_eq!(peak.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn bounded_runs_are_spawned_onto_the_runtime_workers() {
+        let started = Arc::new(std::sync::Barrier::new(2));
+        // Both tasks block their thread until the other arrives, which only
+        // completes if they run on two workers at once rather than one task
+        let threads = run_bounded(
+            2,
+            (0..2).map(|_| {
+                let started = started.clone();
+                async move {
+                    started.wait();
+                    std::thread::current().id()
+                }
+            }),
+        )
+        .await;
+        assert_ne!(threads[0], threads[1]);
+    }
+
+    #[tokio::test]
+    async fn one_progress_event_per_iteration_of_each_benchmark() {
+        let executor = CountingExecutor::default();
+        let (progress, mut events) = tokio::sync::mpsc::channel(8);
+        let iterations = 4;
+        let names = ["a", "b", "c"];
+        let iteration_loop = IterationLoop {
+            iterations,
+            num_ids: 10,
+            progress: Some(progress),
+            ..IterationLoop::default()
+        };
+
+        let reporting = async move {
+            let output = RunOutput::default();
+            for name in names {
+                let mut stats = BenchmarkStats::new(name.to_string(), String::new(), 10);
+                iteration_loop
+                    .run(&executor, &ThreeWarmups, &mut stats, &output)
+                    .await
+                    .unwrap();
+            }
+        };
+        let collecting = async {
+            let mut received = Vec::new();
+            while let Some(event) = events.recv().await {
+                received.push(event);
+            }
+            received
+        };
+        let ((), received) = tokio::join!(reporting, collecting);
+
+        assert_eq!(received.len(), iterations * names.len());
+        let sent: Vec<(&str, usize, usize)> = received
+            .iter()
+            .map(|event| (event.benchmark.as_str(), event.iteration, event.total))
+            .collect();
+        let expected: Vec<(&str, usize, usize)> = names
+            .into_iter()
+            .flat_map(|name| (1..=iterations).map(move |i| (name, i, iterations)))
+            .collect();
+        assert_eq!(sent, expected);
+    }
+
+    /// Executor that raises the interrupt flag during its `interrupt_at`th execution, as
+    /// a Ctrl-C arriving while that iteration runs would
+    struct InterruptingExecutor {
+        interrupt_at: usize,
+        interrupted: Arc<AtomicBool>,
+        executions: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl QueryExecutor for InterruptingExecutor {
+        async fn reset(&self) -> BenchmarkResult<()> {
+            Ok(())
+        }
+
+        async fn execute(
+            &self,
+            _benchmark: &dyn BenchmarkTest,
+            _ids: BenchmarkIds<'_>,
+        ) -> BenchmarkResult<Vec<ExampleData>> {
+            if self.executions.fetch_add(1, Ordering::SeqCst) + 1 == self.interrupt_at {
+                self.interrupted.store(true, Ordering::Relaxed);
+            }
+            Ok(Vec::new())
+        }
+    }
+
+    #[tokio::test]
+    async fn interrupting_mid_loop_keeps_the_finished_runs() {
+        let interrupted = Arc::new(AtomicBool::new(false));
+        let executor = InterruptingExecutor {
+            interrupt_at: 3,
+            interrupted: interrupted.clone(),
+            executions: AtomicUsize::new(0),
+        };
+        let iteration_loop = IterationLoop {
+            iterations: 10,
+            num_ids: 10,
+            interrupted,
+            ..IterationLoop::default()
+        };
+        let output = RunOutput::default();
+        let mut stats = BenchmarkStats::new("interrupted".to_string(), String::new(), 10);
+        let outcome = iteration_loop
+            .run(&executor, &ThreeWarmups, &mut stats, &output)
+            .await
+            .unwrap();
+
+        // The interrupted iteration finishes and counts; no later one starts
+        assert!(outcome.interrupted);
+        assert_eq!(stats.runs.len(), 3);
+        assert_eq!(executor.executions.load(Ordering::SeqCst), 3);
+        let warnings = output.warnings.lock().unwrap();
+        assert_eq!(warnings.len(), 1);
+        assert!(
+            warnings[0]
+                .message
+                .contains("interrupted after 3 of 10 runs")
+        );
+    }
+
+    /// Executor recording the representation of every ID set it is handed
+    #[derive(Default)]
+    struct RecordingExecutor {
+        /// Benchmark, ID kind and the IDs as BIGINT keys, per execution
+        received: Mutex<Vec<(String, IdKind, Vec<i64>)>>,
+    }
+
+    #[async_trait]
+    impl QueryExecutor for RecordingExecutor {
+        async fn reset(&self) -> BenchmarkResult<()> {
+            Ok(())
+        }
+
+        async fn execute(
+            &self,
+            benchmark: &dyn BenchmarkTest,
+            ids: BenchmarkIds<'_>,
+        ) -> BenchmarkResult<Vec<ExampleData>> {
+            let keys = match ids {
+                BenchmarkIds::Bytes(ids) => ids.iter().map(bigint_key_for).collect(),
+                BenchmarkIds::BigInt(ids) => ids.to_vec(),
+            };
+            self.received
+                .lock()
+                .unwrap()
+                .push((benchmark.name().to_string(), ids.kind(), keys));
+            Ok(Vec::new())
+        }
+    }
+
+    #[tokio::test]
+    async fn each_benchmark_receives_the_id_kind_it_asks_for() {
+        let executor = RecordingExecutor::default();
+        let iteration_loop = IterationLoop {
+            iterations: 1,
+            num_ids: 50,
+            ..IterationLoop::default()
+        };
+        let output = RunOutput::default();
+        for benchmark in [
+            &AnyArrayBenchmark as &dyn BenchmarkTest,
+            &UnnestArrayBenchmark,
+        ] {
+            let mut stats = BenchmarkStats::new(benchmark.name().to_string(), String::new(), 50);
+            iteration_loop
+                .run(&executor, benchmark, &mut stats, &output)
+                .await
+                .unwrap();
+        }
+
+        let received = executor.received.lock().unwrap();
+        let kinds: Vec<(&str, IdKind, usize)> = received
+            .iter()
+            .map(|(name, kind, keys)| (name.as_str(), *kind, keys.len()))
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                ("any_array", IdKind::BigInt, 50),
+                ("unnest_array", IdKind::Bytes, 50),
+            ]
+        );
+        // Both representations come from the same seeded logical ID set
+        assert_eq!(received[0].2, received[1].2);
+    }
+
+    /// Executor whose Nth execution fails with a timeout and the rest succeed
+    struct FailsOnExecution {
+        failing: usize,
+        executions: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl QueryExecutor for FailsOnExecution {
+        async fn reset(&self) -> BenchmarkResult<()> {
+            Ok(())
+        }
+
+        async fn execute(
+            &self,
+            _benchmark: &dyn BenchmarkTest,
+            _ids: BenchmarkIds<'_>,
+        ) -> BenchmarkResult<Vec<ExampleData>> {
+            if self.executions.fetch_add(1, Ordering::SeqCst) + 1 == self.failing {
+                return Err(BenchmarkError::Timeout {
+                    limit: Duration::from_secs(1),
+                });
+            }
+            Ok(Vec::new())
+        }
+    }
+
+    #[tokio::test]
+    async fn failed_run_records_the_iteration_that_failed() {
+        let executor = FailsOnExecution {
+            failing: 3,
+            executions: AtomicUsize::new(0),
+        };
+        // No warmups, so the failing execution is the third timed iteration
+        let iteration_loop = IterationLoop {
+            iterations: 5,
+            num_ids: 10,
+            warmup: Some(0),
+            ..IterationLoop::default()
+        };
+        let output = RunOutput::default();
+        let mut stats = BenchmarkStats::new("three_warmups".to_string(), String::new(), 10);
+        iteration_loop
+            .run(&executor, &ThreeWarmups, &mut stats, &output)
+            .await
+            .unwrap();
+
+        assert_eq!(stats.runs.len(), 4);
+        let failures = output.failures.into_inner().unwrap();
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].benchmark, "three_warmups");
+        assert_eq!(failures[0].iteration, 3);
+        assert!(matches!(failures[0].error, BenchmarkError::Timeout { .. }));
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = crate::utils::get_failures_json_path(dir.path());
+        export_failures_to_json(&failures, &path).unwrap();
+        let json: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(json.as_array().unwrap().len(), 1);
+        assert_eq!(json[0]["benchmark"], "three_warmups");
+        assert_eq!(json[0]["iteration"], 3);
+        assert_eq!(json[0]["kind"], "timeout");
+    }
+
+    struct Flaky {
+        failures: usize,
+        runs: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl BenchmarkTest for Flaky {
+        async fn run(
+            &self,
+            _context: &BenchmarkContext,
+            _ids: BenchmarkIds<'_>,
+        ) -> BenchmarkResult<Vec<ExampleData>> {
+            if self.runs.fetch_add(1, Ordering::SeqCst) < self.failures {
+                let reset = std::io::Error::from(std::io::ErrorKind::ConnectionReset);
+                return Err(BenchmarkError::Database(sqlx::Error::Io(reset)));
+            }
+            Ok(vec![ExampleData {
+                response: "ok".to_string(),
+                hash: None,
+            }])
+        }
+
+        fn name(&self) -> &'static str {
+            "flaky"
+        }
+
+        fn description(&self) -> &'static str {
+            "Fails with a reset connection, then succeeds"
+        }
+    }
+
+    #[tokio::test]
+    async fn transient_failures_are_retried_into_one_successful_run() {
+        // The benchmark never touches the pool, and caches aren't reset
+        let pool = sqlx::postgres::PgPoolOptions::new()
+            .connect_lazy("postgres://postgres@127.0.0.1:1/unreachable")
+            .unwrap();
+        let mut context = BenchmarkContext::new(pool);
+        context.disable_cache = false;
+        let retry = RetryConfig {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(1),
+        };
+
+        let benchmark = Flaky {
+            failures: 2,
+            runs: AtomicUsize::new(0),
+        };
+        let mut stats = BenchmarkStats::new("flaky".to_string(), String::new(), 1);
+        stats.attempts += 1;
+        let iteration = run_iteration(
+            &context,
+            &benchmark,
+            BenchmarkIds::Bytes(&[[0u8; 32]]),
+            1,
+            retry,
+        )
+        .await;
+        assert_eq!(iteration.retries, 2);
+        stats.add_result(iteration.duration, iteration.result.unwrap().len());
+        assert_eq!((stats.runs.len(), stats.attempts), (1, 1));
+
+        // A third failure uses up the attempts and is reported
+        let benchmark = Flaky {
+            failures: 3,
+            runs: AtomicUsize::new(0),
+        };
+        let iteration = run_iteration(
+            &context,
+            &benchmark,
+            BenchmarkIds::Bytes(&[[0u8; 32]]),
+            1,
+            retry,
+        )
+        .await;
+        assert_eq!(iteration.retries, 2);
+        assert!(iteration.result.unwrap_err().is_transient());
+        assert_eq!(benchmark.runs.load(Ordering::SeqCst), 3);
+
+        // Errors about the query itself are not retried
+        let query_error = BenchmarkError::Database(sqlx::Error::ColumnNotFound("response".into()));
+        assert!(!query_error.is_transient());
+    }
+
+    /// Executor whose first `failures` executions fail with a reset connection
+    #[derive(Default)]
+    struct FailsThenSucceeds {
+        failures: usize,
+        resets: AtomicUsize,
+        executions: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl QueryExecutor for FailsThenSucceeds {
+        async fn reset(&self) -> BenchmarkResult<()> {
+            self.resets.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+
+        async fn execute(
+            &self,
+            _benchmark: &dyn BenchmarkTest,
+            _ids: BenchmarkIds<'_>,
+        ) -> BenchmarkResult<Vec<ExampleData>> {
+            if self.executions.fetch_add(1, Ordering::SeqCst) < self.failures {
+                let reset = std::io::Error::from(std::io::ErrorKind::ConnectionReset);
+                return Err(BenchmarkError::Database(sqlx::Error::Io(reset)));
+            }
+            Ok(vec![ExampleData {
+                response: "ok".to_string(),
+                hash: None,
+            }])
+        }
+    }
+
+    #[tokio::test]
+    async fn the_loop_retries_a_run_that_fails_twice_then_succeeds() {
+        let executor = FailsThenSucceeds {
+            failures: 2,
+            ..FailsThenSucceeds::default()
+        };
+        let iteration_loop = IterationLoop {
+            iterations: 1,
+            num_ids: 10,
+            retry: RetryConfig {
+                max_attempts: 3,
+                base_delay: Duration::from_millis(1),
+            },
+            ..IterationLoop::default()
+        };
+        let output = RunOutput::default();
+        let mut stats = BenchmarkStats::new("flaky".to_string(), String::new(), 10);
+        let outcome = iteration_loop
+            .run(&executor, &ThreeWarmups, &mut stats, &output)
+            .await
+            .unwrap();
+
+        // Both failures were retried within the one iteration, each from a cache reset
+        assert_eq!((stats.runs.len(), stats.attempts), (1, 1));
+        assert_eq!(stats.rows_returned, 1);
+        assert_eq!(executor.executions.load(Ordering::SeqCst), 3);
+        assert_eq!(executor.resets.load(Ordering::SeqCst), 3);
+        assert_eq!(outcome.failures, 0);
+        assert_eq!(outcome.runs.len(), 1);
+        let (results, warnings, failures) = output.into_parts();
+        assert!(results.is_empty() && warnings.is_empty() && failures.is_empty());
+    }
+
+    #[tokio::test]
+    async fn without_retries_the_two_failures_are_recorded_and_made_up_for() {
+        let executor = FailsThenSucceeds {
+            failures: 2,
+            ..FailsThenSucceeds::default()
+        };
+        let iteration_loop = IterationLoop {
+            iterations: 1,
+            num_ids: 10,
+            retry_failed_to_target: true,
+            abort_on_disconnect: true,
+            ..IterationLoop::default()
+        };
+        let output = RunOutput::default();
+        let mut stats = BenchmarkStats::new("flaky".to_string(), String::new(), 10);
+        let outcome = iteration_loop
+            .run(&executor, &ThreeWarmups, &mut stats, &output)
+            .await
+            .unwrap();
+
+        assert_eq!((stats.runs.len(), stats.attempts), (1, 3));
+        assert_eq!(outcome.failures, 2);
+        assert_eq!(outcome.runs[0].attempt, 3);
+        // Two connection failures stay below the disconnect threshold, and the success resets them
+        assert_eq!(output.connection_failures.load(Ordering::Relaxed), 0);
+        let (_, warnings, failures) = output.into_parts();
+        let failed: Vec<usize> = failures.iter().map(|failure| failure.iteration).collect();
+        assert_eq!(failed, [1, 2]);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].message.starts_with("2 of 3 iterations failed"));
+    }
+
+    #[tokio::test]
+    async fn consecutive_connection_failures_abort_the_loop() {
+        let executor = FailsThenSucceeds {
+            failures: usize::MAX,
+            ..FailsThenSucceeds::default()
+        };
+        let iteration_loop = IterationLoop {
+            iterations: 10,
+            num_ids: 10,
+            abort_on_disconnect: true,
+            ..IterationLoop::default()
+        };
+        let output = RunOutput::default();
+        let mut stats = BenchmarkStats::new("down".to_string(), String::new(), 10);
+        let error = iteration_loop
+            .run(&executor, &ThreeWarmups, &mut stats, &output)
+            .await
+            .unwrap_err();
+
+        assert!(matches!(
+            error,
+            BenchmarkError::Disconnected {
+                failures: DISCONNECT_THRESHOLD,
+                ..
+            }
+        ));
+        assert_eq!(stats.attempts, DISCONNECT_THRESHOLD);
+        assert!(stats.runs.is_empty());
+    }
+
+    #[tokio::test]
+    async fn a_failed_warmup_skips_the_benchmark_with_a_warning() {
+        let executor = FailsThenSucceeds {
+            failures: 1,
+            ..FailsThenSucceeds::default()
+        };
+        let iteration_loop = IterationLoop {
+            iterations: 10,
+            num_ids: 10,
+            ..IterationLoop::default()
+        };
+        let output = RunOutput::default();
+        let warmed_up = iteration_loop
+            .warm_up(&executor, &ThreeWarmups, "three_warmups", &output)
+            .await
+            .unwrap();
+
+        assert!(!warmed_up);
+        assert_eq!(executor.executions.load(Ordering::SeqCst), 1);
+        {
+            let warnings = output.warnings.lock().unwrap();
+            assert_eq!(warnings.len(), 1);
+            assert!(warnings[0].message.starts_with("warmup failed"));
+        }
+
+        // With the warmups overridden to none there is nothing to fail
+        let no_warmups = IterationLoop {
+            warmup: Some(0),
+            ..iteration_loop
+        };
+        assert!(
+            no_warmups
+                .warm_up(&executor, &ThreeWarmups, "three_warmups", &output)
+                .await
+                .unwrap()
+        );
+    }
+
+    /// Needs a database: `DATABASE_URL=postgres://... cargo test -- --ignored`
+    #[tokio::test]
+    #[ignore]
+    async fn a_busy_single_connection_pool_shows_up_as_acquire_time() {
+        use sqlx::postgres::PgPoolOptions;
+
+        let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        let pool = PgPoolOptions::new()
+            .max_connections(1)
+            .connect(&url)
+            .await
+            .unwrap();
+        let mut context = BenchmarkContext::new(pool.clone());
+        context.disable_cache = false;
+        context.measure_acquire = true;
+        let ids = IdSet::new(generate_test_ids(100, ID_RANGE));
+        let ids = ids.get(AnyArrayBenchmark.id_kind());
+
+        // Concurrent load holds the only connection for a while
+        let held = pool.acquire().await.unwrap();
+        let release = tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            drop(held);
+        });
+        let iteration =
+            run_iteration(&context, &AnyArrayBenchmark, ids, 1, RetryConfig::default()).await;
+        release.await.unwrap();
+        iteration.result.unwrap();
+        let acquire = iteration.acquire.expect("acquire time is measured");
+        assert!(acquire >= Duration::from_millis(50), "{:?}", acquire);
+        assert!(acquire <= iteration.duration);
+
+        // Uncontended, the wait is a small part of the run
+        let iteration =
+            run_iteration(&context, &AnyArrayBenchmark, ids, 1, RetryConfig::default()).await;
+        assert!(iteration.acquire.unwrap() < Duration::from_millis(50));
+    }
+}
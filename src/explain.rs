@@ -0,0 +1,182 @@
+//! Query plan capture
+//!
+//! Benchmarks that execute a single final statement can prefix it with `EXPLAIN`
+//! to show how PostgreSQL planned the query. This module holds the shared option
+//! handling and the minimal parsing needed to summarise a JSON plan.
+
+use crate::{BenchmarkError, BenchmarkResult};
+use serde::Deserialize;
+use sqlx::Row;
+use sqlx::postgres::PgRow;
+use std::time::Duration;
+
+/// Output format requested from `EXPLAIN`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExplainFormat {
+    Text,
+    Json,
+}
+
+/// Options controlling the `EXPLAIN` prefix applied to a benchmark's query
+#[derive(Debug, Clone, Copy)]
+pub struct ExplainOptions {
+    pub analyze: bool,
+    pub buffers: bool,
+    pub format: ExplainFormat,
+}
+
+impl ExplainOptions {
+    /// Plan-only JSON output (the query is not executed)
+    pub fn plan_json() -> Self {
+        Self {
+            analyze: false,
+            buffers: false,
+            format: ExplainFormat::Json,
+        }
+    }
+
+    /// Executed JSON output with planning and execution times
+    pub fn analyze_json() -> Self {
+        Self {
+            analyze: true,
+            buffers: false,
+            format: ExplainFormat::Json,
+        }
+    }
+
+    /// Executed text output with buffer usage, for reading the plan directly
+    pub fn analyze_text() -> Self {
+        Self {
+            analyze: true,
+            buffers: true,
+            format: ExplainFormat::Text,
+        }
+    }
+
+    /// Prefix the given query with the configured `EXPLAIN (...)` clause
+    pub fn wrap(&self, query: &str) -> String {
+        let mut options = Vec::new();
+        if self.analyze {
+            options.push("ANALYZE");
+        }
+        if self.buffers {
+            options.push("BUFFERS");
+        }
+        options.push(match self.format {
+            ExplainFormat::Text => "FORMAT TEXT",
+            ExplainFormat::Json => "FORMAT JSON",
+        });
+
+        format!("EXPLAIN ({}) {}", options.join(", "), query)
+    }
+}
+
+/// Join the rows returned by `EXPLAIN` into a single string
+pub fn collect_plan(rows: &[PgRow]) -> BenchmarkResult<String> {
+    let mut lines = Vec::with_capacity(rows.len());
+    for row in rows {
+        // JSON plans come back as a `json` column, text plans as `text`
+        lines.push(row.try_get_unchecked::<String, _>(0)?);
+    }
+    Ok(lines.join("\n"))
+}
+
+/// Top-level entry of `EXPLAIN (FORMAT JSON)` output
+#[derive(Debug, Deserialize)]
+struct ExplainEntry {
+    #[serde(rename = "Plan")]
+    plan: PlanNode,
+    /// Milliseconds, only present with `ANALYZE`
+    #[serde(rename = "Planning Time")]
+    planning_time: Option<f64>,
+    /// Milliseconds, only present with `ANALYZE`
+    #[serde(rename = "Execution Time")]
+    execution_time: Option<f64>,
+}
+
+/// Server-side split of one query's latency, from `EXPLAIN (ANALYZE)`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ServerTiming {
+    /// Iteration (1-based, counting successful runs) the timing was measured after
+    pub run_number: usize,
+    pub planning: Duration,
+    pub execution: Duration,
+}
+
+/// Minimal view of a plan node, enough to walk the tree
+#[derive(Debug, Deserialize)]
+pub struct PlanNode {
+    #[serde(rename = "Node Type")]
+    pub node_type: String,
+    #[serde(rename = "Plans", default)]
+    pub plans: Vec<PlanNode>,
+}
+
+/// Parse the first entry of `EXPLAIN` JSON output
+fn parse_entry(plan_json: &str) -> BenchmarkResult<ExplainEntry> {
+    let entries: Vec<ExplainEntry> =
+        serde_json::from_str(plan_json).map_err(|e| BenchmarkError::BenchmarkFailed {
+            message: format!("Failed to parse EXPLAIN output: {}", e),
+        })?;
+
+    entries
+        .into_iter()
+        .next()
+        .ok_or_else(|| BenchmarkError::BenchmarkFailed {
+            message: "EXPLAIN output contained no plan".to_string(),
+        })
+}
+
+/// Extract the planning and execution times from `EXPLAIN (ANALYZE, FORMAT JSON)`
+///
+/// # Arguments
+/// * `plan_json` - Output of `EXPLAIN (ANALYZE, FORMAT JSON)`
+/// * `run_number` - Iteration the timing belongs to
+///
+/// # Returns
+/// * `BenchmarkResult<ServerTiming>` - Timing, or an error if the output has no times
+///   (e.g. `ANALYZE` was not requested)
+pub fn server_timing(plan_json: &str, run_number: usize) -> BenchmarkResult<ServerTiming> {
+    let entry = parse_entry(plan_json)?;
+    match (entry.planning_time, entry.execution_time) {
+        (Some(planning_ms), Some(execution_ms)) => Ok(ServerTiming {
+            run_number,
+            planning: Duration::from_secs_f64(planning_ms.max(0.0) / 1000.0),
+            execution: Duration::from_secs_f64(execution_ms.max(0.0) / 1000.0),
+        }),
+        _ => Err(BenchmarkError::BenchmarkFailed {
+            message: "EXPLAIN output has no planning/execution time; was ANALYZE set?".to_string(),
+        }),
+    }
+}
+
+/// Nodes that only pass rows through and hide the interesting access path
+const PASSTHROUGH_NODES: &[&str] = &[
+    "Gather",
+    "Gather Merge",
+    "Result",
+    "Materialize",
+    "Limit",
+    "Sort",
+    "Unique",
+];
+
+/// Extract the node type that determines how `overrides` is accessed
+///
+/// Pass-through nodes such as `Gather` are skipped so the result is the first
+/// meaningful node (e.g. `Index Scan`, `Bitmap Heap Scan`, `Seq Scan`, `Hash Join`).
+///
+/// # Arguments
+/// * `plan_json` - Output of `EXPLAIN (FORMAT JSON)`
+///
+/// # Returns
+/// * `BenchmarkResult<String>` - Node type or parse error
+pub fn top_level_node_type(plan_json: &str) -> BenchmarkResult<String> {
+    let mut node = parse_entry(plan_json)?.plan;
+
+    while PASSTHROUGH_NODES.contains(&node.node_type.as_str()) && !node.plans.is_empty() {
+        node = node.plans.swap_remove(0);
+    }
+
+    Ok(node.node_type)
+}
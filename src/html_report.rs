@@ -0,0 +1,205 @@
+//! Standalone HTML report for readers who won't open a CSV
+//!
+//! A summary table and an inline-SVG bar chart of each benchmark's median, in one
+//! file with no scripts or external resources, so it can be mailed or opened offline.
+
+use crate::utils::speedup;
+use crate::{BenchmarkResult, BenchmarkStats};
+use std::io::Write;
+
+/// Width in pixels of the longest bar, the slowest benchmark's
+const BAR_MAX_WIDTH: f64 = 600.0;
+/// Height in pixels of each bar
+const BAR_HEIGHT: usize = 20;
+/// Vertical space in pixels taken by each bar, including the gap below it
+const BAR_SPACING: usize = 28;
+/// Width in pixels reserved left of the bars for benchmark names
+const LABEL_WIDTH: usize = 260;
+/// Width in pixels reserved right of the longest bar for its median
+const VALUE_WIDTH: usize = 120;
+
+/// Benchmark results rendered as an HTML page with a latency chart and a table
+pub struct HtmlReport<'a> {
+    results: Vec<&'a BenchmarkStats>,
+}
+
+impl<'a> HtmlReport<'a> {
+    /// Collect the results sorted by median, fastest first
+    ///
+    /// # Arguments
+    /// * `results` - Statistics of every benchmark, in any order
+    pub fn new(results: &'a [BenchmarkStats]) -> Self {
+        let mut results: Vec<&BenchmarkStats> = results.iter().collect();
+        results.sort_by_key(|r| r.median());
+        Self { results }
+    }
+
+    /// Write the report as a standalone HTML document
+    ///
+    /// # Arguments
+    /// * `writer` - Destination, e.g. a file
+    ///
+    /// # Returns
+    /// * `BenchmarkResult<()>` - Success or IO error
+    pub fn write_html<W: Write>(&self, mut writer: W) -> BenchmarkResult<()> {
+        writeln!(writer, "<!DOCTYPE html>")?;
+        writeln!(writer, "<html lang=\"en\">")?;
+        writeln!(writer, "<head>")?;
+        writeln!(writer, "<meta charset=\"utf-8\">")?;
+        writeln!(writer, "<title>PostgreSQL Query Benchmark Results</title>")?;
+        writeln!(
+            writer,
+            "<style>body {{ font-family: sans-serif; margin: 2em; }} \
+             table {{ border-collapse: collapse; }} \
+             th, td {{ border: 1px solid #ccc; padding: 4px 8px; }} \
+             td.num {{ text-align: right; }}</style>"
+        )?;
+        writeln!(writer, "</head>")?;
+        writeln!(writer, "<body>")?;
+        writeln!(writer, "<h1>PostgreSQL Query Benchmark Results</h1>")?;
+
+        writeln!(writer, "<h2>Median Latency</h2>")?;
+        self.write_chart(&mut writer)?;
+
+        writeln!(writer, "<h2>Summary</h2>")?;
+        writeln!(writer, "<table>")?;
+        writeln!(
+            writer,
+            "<tr><th>Benchmark</th><th>Description</th><th>Runs</th><th>Median</th><th>Mean</th>\
+             <th>P95</th><th>P99</th><th>Std Dev</th><th>Rows</th><th>Input Size</th>\
+             <th>vs Slowest</th></tr>"
+        )?;
+        let slowest = self.slowest_median();
+        for result in &self.results {
+            let vs_slowest = match speedup(slowest, result.median()) {
+                Some(speedup) => format!("{:.2}x", speedup),
+                None => "n/a".to_string(),
+            };
+            writeln!(
+                writer,
+                "<tr><td>{}</td><td>{}</td><td class=\"num\">{}</td><td class=\"num\">{:?}</td>\
+                 <td class=\"num\">{:?}</td><td class=\"num\">{:?}</td><td class=\"num\">{:?}</td>\
+                 <td class=\"num\">{:?}</td><td class=\"num\">{}</td><td class=\"num\">{}</td>\
+                 <td class=\"num\">{}</td></tr>",
+                escape_html(&result.name),
+                escape_html(&result.description),
+                result.runs.len(),
+                result.median(),
+                result.mean(),
+                result.percentile(95.0),
+                result.percentile(99.0),
+                result.std_deviation(),
+                result.rows_returned,
+                result.input_size,
+                vs_slowest
+            )?;
+        }
+        writeln!(writer, "</table>")?;
+
+        writeln!(writer, "</body>")?;
+        writeln!(writer, "</html>")?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Write one horizontal bar per benchmark, scaled to the slowest median
+    fn write_chart<W: Write>(&self, writer: &mut W) -> BenchmarkResult<()> {
+        let width = LABEL_WIDTH + BAR_MAX_WIDTH as usize + VALUE_WIDTH;
+        let height = self.results.len() * BAR_SPACING;
+        writeln!(
+            writer,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" \
+             font-family=\"sans-serif\" font-size=\"12\">",
+            width, height
+        )?;
+
+        let slowest = self.slowest_median().as_nanos() as f64;
+        for (i, result) in self.results.iter().enumerate() {
+            let median = result.median();
+            let bar_width = if slowest > 0.0 {
+                median.as_nanos() as f64 / slowest * BAR_MAX_WIDTH
+            } else {
+                0.0
+            };
+            let y = i * BAR_SPACING;
+            let text_y = y + BAR_HEIGHT / 2;
+            writeln!(
+                writer,
+                "<text x=\"{}\" y=\"{}\" text-anchor=\"end\" dominant-baseline=\"middle\">{}</text>",
+                LABEL_WIDTH - 8,
+                text_y,
+                escape_html(&result.name)
+            )?;
+            writeln!(
+                writer,
+                "<rect x=\"{}\" y=\"{}\" width=\"{:.1}\" height=\"{}\" fill=\"#4c78a8\"><title>{}: {:?}</title></rect>",
+                LABEL_WIDTH,
+                y,
+                bar_width,
+                BAR_HEIGHT,
+                escape_html(&result.name),
+                median
+            )?;
+            writeln!(
+                writer,
+                "<text x=\"{:.1}\" y=\"{}\" dominant-baseline=\"middle\">{:?}</text>",
+                LABEL_WIDTH as f64 + bar_width + 6.0,
+                text_y,
+                median
+            )?;
+        }
+
+        writeln!(writer, "</svg>")?;
+        Ok(())
+    }
+
+    /// Median of the slowest benchmark, the full width of the chart
+    fn slowest_median(&self) -> std::time::Duration {
+        self.results.last().map(|r| r.median()).unwrap_or_default()
+    }
+}
+
+/// Escape text for HTML element content and attribute values
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_stats;
+
+    #[test]
+    fn writes_one_bar_and_one_row_per_benchmark() {
+        let results = vec![
+            test_stats("unnest_array", &[15_000, 15_010]),
+            test_stats("any_array", &[12_000, 12_010]),
+            test_stats("raw_sql<script>", &[30_000, 30_010]),
+        ];
+        let mut out = Vec::new();
+        HtmlReport::new(&results).write_html(&mut out).unwrap();
+        let html = String::from_utf8(out).unwrap();
+
+        assert!(html.contains("<svg"));
+        assert_eq!(html.matches("<rect").count(), results.len());
+        assert!(html.contains("<table>"));
+        // One header row plus one row per benchmark
+        assert_eq!(html.matches("<tr>").count(), results.len() + 1);
+
+        // The slowest benchmark's bar spans the whole chart
+        assert!(html.contains(&format!("width=\"{:.1}\"", BAR_MAX_WIDTH)));
+        assert!(html.contains("raw_sql&lt;script&gt;"));
+        assert!(!html.contains("<script>"));
+    }
+}
@@ -0,0 +1,212 @@
+//! Key types other than the 32-byte hash
+//!
+//! The harness generates SHA-256 hashes. Every other key type is derived from a
+//! hash's leading bytes with the same expression `scripts/setup_test_db.sql` uses to
+//! fill that type's `overrides_<type>_key` table, so each key type looks up the same
+//! logical rows and the key-type benchmarks stay comparable.
+
+use crate::copy::BinaryCopyWriter;
+use chrono::{DateTime, NaiveDateTime};
+use sqlx::Postgres;
+use sqlx::postgres::PgArguments;
+use sqlx::query::{Query, QueryAs};
+use sqlx::types::Uuid;
+
+/// Microseconds from the Unix epoch to PostgreSQL's epoch, 2000-01-01 00:00:00
+pub const PG_EPOCH_UNIX_MICROS: i64 = 946_684_800_000_000;
+
+/// Bits of the hash used as the TIMESTAMP key's offset from the PostgreSQL epoch
+///
+/// 52 bits of microseconds span about 142 years, and stay exact when the setup
+/// script multiplies them into an interval (which goes through a double).
+const TIMESTAMP_KEY_BITS: u32 = 52;
+
+/// Key column type compared by the key-type benchmarks
+///
+/// Each variant queries its own table, created by `scripts/setup_test_db.sql`. The
+/// tables share a shape and contents so the only difference is the key's width and
+/// type; comparing against a column of a different type would add casts and
+/// invalidate the comparison.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyType {
+    /// 32-byte SHA-256 hashes bound as `bytea[]`
+    Bytea,
+    /// 8-byte integers bound as `bigint[]`
+    Bigint,
+    /// 16-byte UUIDs bound as `uuid[]`
+    Uuid,
+    /// Timestamps without time zone bound as `timestamp[]`
+    Timestamp,
+}
+
+impl KeyType {
+    /// Every supported key type
+    pub const ALL: [KeyType; 4] = [
+        KeyType::Bytea,
+        KeyType::Bigint,
+        KeyType::Uuid,
+        KeyType::Timestamp,
+    ];
+
+    /// SQL type of the key column
+    pub fn sql_type(self) -> &'static str {
+        match self {
+            KeyType::Bytea => "BYTEA",
+            KeyType::Bigint => "BIGINT",
+            KeyType::Uuid => "UUID",
+            KeyType::Timestamp => "TIMESTAMP",
+        }
+    }
+
+    /// Rust type the key is bound as
+    pub fn rust_type(self) -> &'static str {
+        match self {
+            KeyType::Bytea => "[u8; 32]",
+            KeyType::Bigint => "i64",
+            KeyType::Uuid => "Uuid",
+            KeyType::Timestamp => "NaiveDateTime",
+        }
+    }
+
+    /// Table keyed by this type
+    pub fn table(self) -> &'static str {
+        match self {
+            KeyType::Bytea => "overrides_bytea_key",
+            KeyType::Bigint => "overrides_bigint_key",
+            KeyType::Uuid => "overrides_uuid_key",
+            KeyType::Timestamp => "overrides_timestamp_key",
+        }
+    }
+
+    /// Length of one key's binary COPY payload in bytes
+    pub fn encoded_len(self) -> usize {
+        match self {
+            KeyType::Bytea => std::mem::size_of::<[u8; 32]>(),
+            KeyType::Bigint | KeyType::Timestamp => std::mem::size_of::<i64>(),
+            KeyType::Uuid => std::mem::size_of::<Uuid>(),
+        }
+    }
+}
+
+/// Map a hash to its BIGINT key: the first 8 bytes, big-endian
+///
+/// Matches `('x' || encode(substring(hash FROM 1 FOR 8), 'hex'))::bit(64)::bigint`
+/// in the setup script.
+pub fn bigint_key_for(id: &[u8; 32]) -> i64 {
+    let mut prefix = [0u8; 8];
+    prefix.copy_from_slice(&id[..8]);
+    i64::from_be_bytes(prefix)
+}
+
+/// Map a hash to its UUID key: the first 16 bytes
+///
+/// Matches `encode(substring(hash FROM 1 FOR 16), 'hex')::uuid` in the setup script.
+pub fn uuid_key_for(id: &[u8; 32]) -> Uuid {
+    let mut prefix = [0u8; 16];
+    prefix.copy_from_slice(&id[..16]);
+    Uuid::from_bytes(prefix)
+}
+
+/// Map a hash to its TIMESTAMP key: the first 52 bits as microseconds after 2000-01-01
+///
+/// Matches `timestamp '2000-01-01' + ('x' || substring(encode(hash, 'hex') FROM 1 FOR 13))::bit(52)::bigint * interval '1 microsecond'`
+/// in the setup script.
+pub fn timestamp_key_for(id: &[u8; 32]) -> NaiveDateTime {
+    let offset = (bigint_key_for(id) as u64) >> (64 - TIMESTAMP_KEY_BITS);
+    DateTime::from_timestamp_micros(PG_EPOCH_UNIX_MICROS + offset as i64)
+        .expect("a 52-bit offset from 2000-01-01 is within chrono's range")
+        .naive_utc()
+}
+
+/// One ID set converted to a key type
+///
+/// BYTEA keys borrow the hashes unchanged; the other types are derived up front.
+/// A real schema would already hold its keys, so the conversion is kept out of the
+/// database round trip but not out of the timed run.
+#[derive(Debug, Clone)]
+pub enum Keys<'a> {
+    Bytea(&'a [[u8; 32]]),
+    Bigint(Vec<i64>),
+    Uuid(Vec<Uuid>),
+    Timestamp(Vec<NaiveDateTime>),
+}
+
+impl<'a> Keys<'a> {
+    /// Convert hashes to keys of the given type
+    ///
+    /// # Arguments
+    /// * `key_type` - Type to convert to
+    /// * `ids` - Hashes from [`crate::utils::generate_test_ids`]
+    ///
+    /// # Returns
+    /// * `Keys` - One key per hash, in the same order
+    pub fn from_ids(key_type: KeyType, ids: &'a [[u8; 32]]) -> Self {
+        match key_type {
+            KeyType::Bytea => Keys::Bytea(ids),
+            KeyType::Bigint => Keys::Bigint(ids.iter().map(bigint_key_for).collect()),
+            KeyType::Uuid => Keys::Uuid(ids.iter().map(uuid_key_for).collect()),
+            KeyType::Timestamp => Keys::Timestamp(ids.iter().map(timestamp_key_for).collect()),
+        }
+    }
+
+    /// Type of the keys
+    pub fn key_type(&self) -> KeyType {
+        match self {
+            Keys::Bytea(_) => KeyType::Bytea,
+            Keys::Bigint(_) => KeyType::Bigint,
+            Keys::Uuid(_) => KeyType::Uuid,
+            Keys::Timestamp(_) => KeyType::Timestamp,
+        }
+    }
+
+    /// Number of keys
+    pub fn len(&self) -> usize {
+        match self {
+            Keys::Bytea(keys) => keys.len(),
+            Keys::Bigint(keys) => keys.len(),
+            Keys::Uuid(keys) => keys.len(),
+            Keys::Timestamp(keys) => keys.len(),
+        }
+    }
+
+    /// Whether there are no keys
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Bind the keys as a single array parameter of a typed query
+    pub fn bind_query_as<O>(
+        self,
+        query: QueryAs<'a, Postgres, O, PgArguments>,
+    ) -> QueryAs<'a, Postgres, O, PgArguments> {
+        match self {
+            Keys::Bytea(keys) => query.bind(keys),
+            Keys::Bigint(keys) => query.bind(keys),
+            Keys::Uuid(keys) => query.bind(keys),
+            Keys::Timestamp(keys) => query.bind(keys),
+        }
+    }
+
+    /// Bind the keys as a single array parameter of an untyped query
+    pub fn bind_query(
+        self,
+        query: Query<'a, Postgres, PgArguments>,
+    ) -> Query<'a, Postgres, PgArguments> {
+        match self {
+            Keys::Bytea(keys) => query.bind(keys),
+            Keys::Bigint(keys) => query.bind(keys),
+            Keys::Uuid(keys) => query.bind(keys),
+            Keys::Timestamp(keys) => query.bind(keys),
+        }
+    }
+
+    /// Append one single-column tuple per key to a binary COPY payload
+    pub fn write_copy(&self, writer: &mut BinaryCopyWriter) {
+        match self {
+            Keys::Bytea(keys) => keys.iter().for_each(|key| writer.write_tuple(&[key])),
+            Keys::Bigint(keys) => keys.iter().for_each(|key| writer.write_tuple(&[key])),
+            Keys::Uuid(keys) => keys.iter().for_each(|key| writer.write_tuple(&[key])),
+            Keys::Timestamp(keys) => keys.iter().for_each(|key| writer.write_tuple(&[key])),
+        }
+    }
+}
@@ -0,0 +1,136 @@
+//! The `list` subcommand's benchmark listing
+
+use crate::keys::KeyType;
+use crate::{BenchmarkContext, BenchmarkResult, BenchmarkTest};
+use std::io::Write;
+use std::sync::Arc;
+
+/// IDs the verbose listing renders each query for; few enough that inlined literals stay readable
+const LIST_QUERY_IDS: usize = 3;
+
+/// Write one line per benchmark: name, description, non-BYTEA key type and tags
+///
+/// With `verbose`, each benchmark is followed by its ID representation, whether it
+/// loads a temp table, its warmup runs and its final query for [`LIST_QUERY_IDS`] IDs.
+///
+/// # Arguments
+/// * `writer` - Destination, e.g. stdout
+/// * `benchmarks` - Benchmarks to list, in order
+/// * `context` - Context whose schema and query template the queries are rendered with
+/// * `verbose` - Whether to add the details below each benchmark
+///
+/// # Returns
+/// * `BenchmarkResult<()>` - Success or IO error
+pub fn write_benchmark_list<W: Write>(
+    mut writer: W,
+    benchmarks: &[Arc<dyn BenchmarkTest>],
+    context: &BenchmarkContext,
+    verbose: bool,
+) -> BenchmarkResult<()> {
+    writeln!(writer, "Available benchmarks:")?;
+    for benchmark in benchmarks {
+        let key = match benchmark.key_type() {
+            KeyType::Bytea => String::new(),
+            key_type => format!(" (key: {})", key_type.sql_type()),
+        };
+        if benchmark.tags().is_empty() {
+            writeln!(
+                writer,
+                "  {}: {}{}",
+                benchmark.name(),
+                benchmark.description(),
+                key
+            )?;
+        } else {
+            writeln!(
+                writer,
+                "  {}: {}{} [{}]",
+                benchmark.name(),
+                benchmark.description(),
+                key,
+                benchmark.tags().join(", ")
+            )?;
+        }
+
+        if verbose {
+            let key_type = benchmark.key_type();
+            writeln!(
+                writer,
+                "      IDs: {} bound as {}",
+                key_type.rust_type(),
+                key_type.sql_type()
+            )?;
+            writeln!(
+                writer,
+                "      Temp table: {}",
+                if benchmark.uses_temp_table() {
+                    "yes"
+                } else {
+                    "no"
+                }
+            )?;
+            match benchmark.warmup_iterations() {
+                0 => writeln!(writer, "      Warmup: none")?,
+                runs => writeln!(writer, "      Warmup: {} runs", runs)?,
+            }
+            match benchmark.rendered_query(context, LIST_QUERY_IDS) {
+                Some(query) => writeln!(writer, "      Query: {}", query)?,
+                None => writeln!(writer, "      Query: none (no single final statement)")?,
+            }
+        }
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::benchmarks::get_all_benchmarks;
+    use sqlx::postgres::PgPoolOptions;
+
+    #[tokio::test]
+    async fn verbose_listing_shows_the_id_type_and_query() {
+        // Rendering queries never connects
+        let pool = PgPoolOptions::new()
+            .connect_lazy("postgres://postgres@127.0.0.1:1/unreachable")
+            .unwrap();
+        let context = BenchmarkContext::new(pool);
+        let benchmarks = get_all_benchmarks();
+
+        let mut out = Vec::new();
+        write_benchmark_list(&mut out, &benchmarks, &context, false).unwrap();
+        let short = String::from_utf8(out).unwrap();
+        assert_eq!(short.lines().count(), benchmarks.len() + 1);
+        assert!(!short.contains("IDs:"));
+
+        let mut out = Vec::new();
+        write_benchmark_list(&mut out, &benchmarks, &context, true).unwrap();
+        let verbose = String::from_utf8(out).unwrap();
+        // A benchmark's line and the detail lines below it
+        let entry = |name: &str| {
+            let lines: Vec<&str> = verbose.lines().collect();
+            let start = lines
+                .iter()
+                .position(|line| line.starts_with(&format!("  {}:", name)))
+                .unwrap();
+            lines[start..start + 5].join("\n")
+        };
+        let unnest_array = entry("unnest_array");
+        assert!(unnest_array.contains("IDs: [u8; 32] bound as BYTEA"));
+        assert!(unnest_array.contains("Temp table: no"));
+        assert!(unnest_array.contains("Query: SELECT response FROM overrides WHERE hash"));
+
+        let any_array = entry("any_array");
+        assert!(any_array.contains("(key: BIGINT)"));
+        assert!(any_array.contains("IDs: i64 bound as BIGINT"));
+        assert!(
+            any_array
+                .contains("Query: SELECT response FROM overrides_bigint_key WHERE key = ANY($1)")
+        );
+
+        let bigint = entry("key_type_copy_bigint");
+        assert!(bigint.contains("IDs: i64 bound as BIGINT"));
+        assert!(bigint.contains("Temp table: yes"));
+    }
+}
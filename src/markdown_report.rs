@@ -0,0 +1,119 @@
+//! GitHub-flavored Markdown summary for pasting into pull requests
+//!
+//! One table row per benchmark, sorted by median, followed by a ranked list with
+//! each benchmark's speedup over the slowest and its description.
+
+use crate::utils::speedup;
+use crate::{BenchmarkResult, BenchmarkStats};
+use std::io::Write;
+
+/// Benchmark results rendered as a Markdown table and ranking
+pub struct MarkdownReport<'a> {
+    results: Vec<&'a BenchmarkStats>,
+}
+
+impl<'a> MarkdownReport<'a> {
+    /// Collect the results sorted by median, fastest first
+    ///
+    /// # Arguments
+    /// * `results` - Statistics of every benchmark, in any order
+    pub fn new(results: &'a [BenchmarkStats]) -> Self {
+        let mut results: Vec<&BenchmarkStats> = results.iter().collect();
+        results.sort_by_key(|r| r.median());
+        Self { results }
+    }
+
+    /// Write the report as Markdown
+    ///
+    /// # Arguments
+    /// * `writer` - Destination, e.g. a file
+    ///
+    /// # Returns
+    /// * `BenchmarkResult<()>` - Success or IO error
+    pub fn write_markdown<W: Write>(&self, mut writer: W) -> BenchmarkResult<()> {
+        writeln!(writer, "## PostgreSQL Query Benchmark Results")?;
+        writeln!(writer)?;
+        writeln!(
+            writer,
+            "| Benchmark | Runs | Median | Mean | P95 | P99 | Std Dev | Rows | Rows/s | Input Size |"
+        )?;
+        writeln!(
+            writer,
+            "|-----------|-----:|-------:|-----:|----:|----:|--------:|-----:|-------:|-----------:|"
+        )?;
+        for result in &self.results {
+            writeln!(
+                writer,
+                "| {} | {} | {:?} | {:?} | {:?} | {:?} | {:?} | {} | {:.0} | {} |",
+                escape_cell(&result.name),
+                result.runs.len(),
+                result.median(),
+                result.mean(),
+                result.percentile(95.0),
+                result.percentile(99.0),
+                result.std_deviation(),
+                result.rows_returned,
+                result.throughput(),
+                result.input_size
+            )?;
+        }
+        writeln!(writer)?;
+
+        writeln!(writer, "### Ranking (by median)")?;
+        writeln!(writer)?;
+        let slowest = self.results.last().map(|r| r.median()).unwrap_or_default();
+        for (i, result) in self.results.iter().enumerate() {
+            let vs_slowest = match speedup(slowest, result.median()) {
+                Some(speedup) => format!("{:.2}x", speedup),
+                None => "n/a".to_string(),
+            };
+            writeln!(
+                writer,
+                "{}. **{}** - {:?} ({} vs slowest) - {}",
+                i + 1,
+                escape_cell(&result.name),
+                result.median(),
+                vs_slowest,
+                escape_cell(&result.description)
+            )?;
+        }
+
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Escape text for a Markdown table cell: pipes would end the cell, newlines the row
+fn escape_cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', " ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_stats;
+
+    #[test]
+    fn writes_one_row_per_benchmark_sorted_by_median() {
+        let mut results = vec![
+            test_stats("unnest_array", &[15_000, 15_010]),
+            test_stats("any_array", &[12_000, 12_010]),
+        ];
+        results[0].description = "UNNEST | join".to_string();
+        let mut out = Vec::new();
+        MarkdownReport::new(&results)
+            .write_markdown(&mut out)
+            .unwrap();
+        let markdown = String::from_utf8(out).unwrap();
+
+        assert!(markdown.contains("| Benchmark |"));
+        let rows: Vec<&str> = markdown
+            .lines()
+            .filter(|line| line.starts_with("| ") && !line.starts_with("| Benchmark"))
+            .collect();
+        assert_eq!(rows.len(), 2);
+        assert!(rows[0].starts_with("| any_array |"));
+        assert!(rows[1].starts_with("| unnest_array |"));
+        assert!(markdown.contains("UNNEST \\| join"));
+    }
+}
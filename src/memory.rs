@@ -0,0 +1,110 @@
+//! Client-side allocation tracking
+//!
+//! Enabled with the `mem-profiling` feature, which installs [`CountingAllocator`] as
+//! the global allocator. It wraps the system allocator and keeps the process's live
+//! heap bytes and their high-water mark, so [`AllocationScope`] can report how far the
+//! heap grew while a benchmark ran. This shows client-side costs the timings hide,
+//! such as `raw_sql_large_in` building its whole IN list as one string.
+//!
+//! The counters are process wide: allocations by other threads (sqlx's background
+//! tasks, concurrent benchmarks) during a scope are counted too, so peaks are only
+//! meaningful when one benchmark runs at a time. Counting adds two atomic operations
+//! to every allocation, a small overhead on the measured durations.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// Heap bytes currently allocated
+static LIVE: AtomicUsize = AtomicUsize::new(0);
+
+/// Highest value of [`LIVE`] since the last [`AllocationScope::start`]
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+/// System allocator that counts live bytes and their peak
+pub struct CountingAllocator;
+
+impl CountingAllocator {
+    fn allocated(size: usize) {
+        let live = LIVE.fetch_add(size, Ordering::Relaxed) + size;
+        PEAK.fetch_max(live, Ordering::Relaxed);
+    }
+
+    fn freed(size: usize) {
+        LIVE.fetch_sub(size, Ordering::Relaxed);
+    }
+}
+
+// SAFETY: every call is forwarded unchanged to the system allocator; the counters
+// only observe the sizes.
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = unsafe { System.alloc(layout) };
+        if !ptr.is_null() {
+            Self::allocated(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = unsafe { System.alloc_zeroed(layout) };
+        if !ptr.is_null() {
+            Self::allocated(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) };
+        Self::freed(layout.size());
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = unsafe { System.realloc(ptr, layout, new_size) };
+        if !new_ptr.is_null() {
+            Self::freed(layout.size());
+            Self::allocated(new_size);
+        }
+        new_ptr
+    }
+}
+
+/// Measures how far the heap grows between [`AllocationScope::start`] and
+/// [`AllocationScope::finish`]
+pub struct AllocationScope {
+    baseline: usize,
+}
+
+impl AllocationScope {
+    /// Start measuring from the current live heap size
+    ///
+    /// Resets the process-wide peak, so scopes must not overlap.
+    pub fn start() -> Self {
+        let baseline = LIVE.load(Ordering::Relaxed);
+        PEAK.store(baseline, Ordering::Relaxed);
+        Self { baseline }
+    }
+
+    /// Stop measuring
+    ///
+    /// # Returns
+    /// * `usize` - Peak live heap bytes above the starting size
+    pub fn finish(self) -> usize {
+        PEAK.load(Ordering::Relaxed).saturating_sub(self.baseline)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scope_sees_memory_freed_before_it_finishes() {
+        let scope = AllocationScope::start();
+        let buffer = vec![1u8; 1 << 20];
+        drop(std::hint::black_box(buffer));
+        assert!(scope.finish() >= 1 << 20);
+    }
+}
@@ -0,0 +1,188 @@
+//! Background activity monitoring
+//!
+//! Autovacuum workers and checkpoints compete with the benchmark for I/O and show up
+//! as latency spikes. A [`BackgroundMonitor`] periodically samples the checkpoint
+//! counters and the number of running autovacuum workers while a benchmark runs, so
+//! slow iterations can be matched against background activity afterwards.
+
+use crate::{BenchmarkResult, BenchmarkStats};
+use sqlx::postgres::PgPool;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+use tracing::warn;
+
+/// How often background activity is sampled
+pub const SAMPLE_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Percentile above which an iteration counts as slow
+pub const SLOW_PERCENTILE: f64 = 95.0;
+
+/// First server version exposing checkpoint counters in `pg_stat_checkpointer`
+const PG_STAT_CHECKPOINTER_VERSION: i32 = 170000;
+
+/// One observation of the server's background activity
+#[derive(Debug, Clone, Copy)]
+struct BackgroundSample {
+    at: Instant,
+    /// Cumulative timed + requested checkpoints
+    checkpoints: i64,
+    /// Autovacuum workers running at the time of the sample
+    autovacuum_workers: i64,
+}
+
+/// Background activity observed during one benchmark
+#[derive(Debug, Clone, Default)]
+pub struct BackgroundReport {
+    /// Checkpoints started during the benchmark window
+    pub checkpoints: i64,
+    /// Whether any autovacuum worker was seen running
+    pub autovacuum_seen: bool,
+    /// Iterations slower than the [`SLOW_PERCENTILE`] percentile
+    pub slow_iterations: usize,
+    /// Slow iterations that overlapped a checkpoint or autovacuum run
+    pub slow_with_activity: usize,
+}
+
+impl BackgroundReport {
+    /// Whether anything happened in the background during the benchmark
+    pub fn has_activity(&self) -> bool {
+        self.checkpoints > 0 || self.autovacuum_seen
+    }
+}
+
+/// Samples background activity on a separate task until stopped
+pub struct BackgroundMonitor {
+    samples: Arc<Mutex<Vec<BackgroundSample>>>,
+    stop: oneshot::Sender<()>,
+    task: JoinHandle<()>,
+}
+
+impl BackgroundMonitor {
+    /// Start sampling background activity
+    ///
+    /// Each sample is one lightweight catalog query on a pooled connection.
+    ///
+    /// # Arguments
+    /// * `pool` - Pool to sample from
+    ///
+    /// # Returns
+    /// * `BenchmarkResult<Self>` - Running monitor, or an error if the first sample fails
+    pub async fn start(pool: PgPool) -> BenchmarkResult<Self> {
+        let version: i32 = sqlx::query_scalar("SELECT current_setting('server_version_num')::int;")
+            .fetch_one(&pool)
+            .await?;
+        let checkpoints = if version >= PG_STAT_CHECKPOINTER_VERSION {
+            "SELECT num_timed + num_requested FROM pg_stat_checkpointer"
+        } else {
+            "SELECT checkpoints_timed + checkpoints_req FROM pg_stat_bgwriter"
+        };
+        let query = format!(
+            "SELECT ({})::bigint, \
+             (SELECT count(*) FROM pg_stat_activity WHERE backend_type = 'autovacuum worker');",
+            checkpoints
+        );
+
+        // Take the first sample up front so the window has a baseline
+        let first = sample(&pool, &query).await?;
+        let samples = Arc::new(Mutex::new(vec![first]));
+
+        let (stop, mut stopped) = oneshot::channel();
+        let task_samples = Arc::clone(&samples);
+        let task = tokio::spawn(async move {
+            let mut interval = tokio::time::interval(SAMPLE_INTERVAL);
+            loop {
+                tokio::select! {
+                    _ = interval.tick() => {}
+                    _ = &mut stopped => break,
+                }
+                match sample(&pool, &query).await {
+                    Ok(sample) => task_samples.lock().unwrap().push(sample),
+                    Err(e) => warn!("Failed to sample background activity: {}", e),
+                }
+            }
+
+            // Close the window with a final sample
+            if let Ok(sample) = sample(&pool, &query).await {
+                task_samples.lock().unwrap().push(sample);
+            }
+        });
+
+        Ok(Self {
+            samples,
+            stop,
+            task,
+        })
+    }
+
+    /// Stop sampling and correlate the samples with the benchmark's iterations
+    ///
+    /// # Arguments
+    /// * `iterations` - Start time of each successful iteration, in the same order as `stats.runs`
+    /// * `stats` - Statistics of the benchmark that was monitored
+    ///
+    /// # Returns
+    /// * `BackgroundReport` - Summary of background activity during the benchmark
+    pub async fn finish(self, iterations: &[Instant], stats: &BenchmarkStats) -> BackgroundReport {
+        let _ = self.stop.send(());
+        if let Err(e) = self.task.await {
+            warn!("Background monitor task failed: {}", e);
+        }
+        let samples = self.samples.lock().unwrap();
+
+        let mut report = BackgroundReport {
+            checkpoints: match (samples.first(), samples.last()) {
+                (Some(first), Some(last)) => last.checkpoints - first.checkpoints,
+                _ => 0,
+            },
+            autovacuum_seen: samples.iter().any(|s| s.autovacuum_workers > 0),
+            ..Default::default()
+        };
+
+        let threshold = stats.percentile(SLOW_PERCENTILE);
+        for (start, duration) in iterations.iter().zip(&stats.runs) {
+            if *duration <= threshold {
+                continue;
+            }
+            report.slow_iterations += 1;
+            if activity_between(&samples, *start, *start + *duration) {
+                report.slow_with_activity += 1;
+            }
+        }
+
+        report
+    }
+}
+
+/// Take one sample of background activity
+async fn sample(pool: &PgPool, query: &str) -> BenchmarkResult<BackgroundSample> {
+    let (checkpoints, autovacuum_workers): (i64, i64) =
+        sqlx::query_as(query).fetch_one(pool).await?;
+    Ok(BackgroundSample {
+        at: Instant::now(),
+        checkpoints,
+        autovacuum_workers,
+    })
+}
+
+/// Whether a checkpoint or autovacuum run overlapped the given window
+///
+/// The window is widened to the nearest samples on either side, since activity
+/// between two samples can't be placed more precisely.
+fn activity_between(samples: &[BackgroundSample], start: Instant, end: Instant) -> bool {
+    let first = samples.iter().rposition(|s| s.at <= start).unwrap_or(0);
+    let last = samples
+        .iter()
+        .position(|s| s.at >= end)
+        .unwrap_or(samples.len().saturating_sub(1));
+    let Some(window) = samples.get(first..=last) else {
+        return false;
+    };
+
+    let checkpoint = match (window.first(), window.last()) {
+        (Some(first), Some(last)) => last.checkpoints > first.checkpoints,
+        _ => false,
+    };
+    checkpoint || window.iter().any(|s| s.autovacuum_workers > 0)
+}
@@ -0,0 +1,168 @@
+//! Result ordering comparison across strategies
+//!
+//! None of the benchmark queries has an `ORDER BY`, so the order rows come back in
+//! depends on the plan: an index scan driven by an `IN` list and a hash join against a
+//! temp table can return the same rows in different sequences. Every benchmark replays
+//! the same ID sets, so the responses to the first ID set can be compared across
+//! strategies as sequences, not just as multisets. Running that ID set a second time
+//! shows whether a strategy's order is stable between executions.
+
+use crate::ExampleData;
+
+/// Responses returned for the first ID set, in the order they were returned
+#[derive(Debug, Clone)]
+pub struct OrderingSample {
+    /// Responses from the first timed iteration
+    pub first: Vec<String>,
+    /// Responses from an untimed repeat with the same IDs, if it succeeded
+    pub repeat: Option<Vec<String>>,
+}
+
+impl OrderingSample {
+    /// Capture the responses of the first timed iteration
+    pub fn new(results: &[ExampleData]) -> Self {
+        Self {
+            first: responses(results),
+            repeat: None,
+        }
+    }
+
+    /// Record the responses of the repeat run
+    pub fn record_repeat(&mut self, results: &[ExampleData]) {
+        self.repeat = Some(responses(results));
+    }
+
+    /// Whether both executions returned rows in the same order
+    ///
+    /// # Returns
+    /// * `Option<bool>` - `None` if the repeat run did not succeed
+    pub fn stable(&self) -> Option<bool> {
+        self.repeat.as_ref().map(|repeat| *repeat == self.first)
+    }
+}
+
+/// How one strategy's rows compare with the reference strategy's
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OrderMatch {
+    /// Same rows in the same order
+    Same,
+    /// Same rows in a different order
+    Reordered,
+    /// Different rows; the order can't be compared
+    DifferentRows,
+}
+
+/// Ordering comparison for one strategy
+#[derive(Debug, Clone)]
+pub struct OrderingComparison {
+    pub name: String,
+    /// Comparison with the reference strategy's rows
+    pub order: OrderMatch,
+    /// Whether the order was the same across both executions
+    pub stable: Option<bool>,
+}
+
+/// Compare each strategy's row order with the first strategy's
+///
+/// # Arguments
+/// * `samples` - Benchmark name and ordering sample, the reference first
+///
+/// # Returns
+/// * `Vec<OrderingComparison>` - One entry per sample, in the same order
+pub fn compare_orderings(samples: &[(&str, &OrderingSample)]) -> Vec<OrderingComparison> {
+    let Some((_, reference)) = samples.first() else {
+        return Vec::new();
+    };
+    let mut reference_sorted = reference.first.clone();
+    reference_sorted.sort_unstable();
+
+    samples
+        .iter()
+        .map(|(name, sample)| {
+            let order = if sample.first == reference.first {
+                OrderMatch::Same
+            } else {
+                let mut sorted = sample.first.clone();
+                sorted.sort_unstable();
+                if sorted == reference_sorted {
+                    OrderMatch::Reordered
+                } else {
+                    OrderMatch::DifferentRows
+                }
+            };
+            OrderingComparison {
+                name: name.to_string(),
+                order,
+                stable: sample.stable(),
+            }
+        })
+        .collect()
+}
+
+/// Extract the responses in the order they were returned
+fn responses(results: &[ExampleData]) -> Vec<String> {
+    results.iter().map(|row| row.response.clone()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rows(responses: &[&str]) -> Vec<ExampleData> {
+        responses
+            .iter()
+            .map(|response| ExampleData {
+                response: response.to_string(),
+                hash: None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn sequences_compare_as_same_reordered_or_different_rows() {
+        let reference = OrderingSample::new(&rows(&["a", "b", "c"]));
+        let identical = OrderingSample::new(&rows(&["a", "b", "c"]));
+        let permuted = OrderingSample::new(&rows(&["c", "a", "b"]));
+        let differing = OrderingSample::new(&rows(&["a", "b", "d"]));
+        // Rows compare as a multiset, so an extra copy is a different result
+        let duplicated = OrderingSample::new(&rows(&["a", "b", "c", "a"]));
+
+        let comparisons = compare_orderings(&[
+            ("in_list", &reference),
+            ("any_array", &identical),
+            ("temp_table_join", &permuted),
+            ("raw_sql", &differing),
+            ("values_join", &duplicated),
+        ]);
+        let orders: Vec<(&str, &OrderMatch)> = comparisons
+            .iter()
+            .map(|c| (c.name.as_str(), &c.order))
+            .collect();
+        assert_eq!(
+            orders,
+            [
+                ("in_list", &OrderMatch::Same),
+                ("any_array", &OrderMatch::Same),
+                ("temp_table_join", &OrderMatch::Reordered),
+                ("raw_sql", &OrderMatch::DifferentRows),
+                ("values_join", &OrderMatch::DifferentRows),
+            ]
+        );
+        assert!(compare_orderings(&[]).is_empty());
+    }
+
+    #[test]
+    fn stable_needs_a_repeat_in_the_same_order() {
+        let mut sample = OrderingSample::new(&rows(&["a", "b"]));
+        assert_eq!(sample.stable(), None);
+
+        sample.record_repeat(&rows(&["a", "b"]));
+        assert_eq!(sample.stable(), Some(true));
+
+        sample.record_repeat(&rows(&["b", "a"]));
+        assert_eq!(sample.stable(), Some(false));
+
+        let comparisons = compare_orderings(&[("any_array", &sample)]);
+        assert_eq!(comparisons[0].stable, Some(false));
+    }
+}
@@ -0,0 +1,176 @@
+//! OpenTelemetry (OTLP) export of benchmark results
+//!
+//! Enabled with the `otlp` feature. Each benchmark's aggregate statistics are
+//! recorded as gauges and, optionally, every iteration becomes a child span of a
+//! per-benchmark span. Export problems such as an unreachable collector are logged
+//! as warnings and never fail the run.
+
+use crate::{BenchmarkError, BenchmarkResult, BenchmarkStats};
+use opentelemetry::metrics::{Gauge, MeterProvider as _};
+use opentelemetry::trace::{Span, TraceContextExt, Tracer, TracerProvider as _};
+use opentelemetry::{Context, KeyValue};
+use opentelemetry_otlp::{MetricExporter, SpanExporter, WithExportConfig};
+use opentelemetry_sdk::Resource;
+use opentelemetry_sdk::metrics::SdkMeterProvider;
+use opentelemetry_sdk::trace::{SdkTracer, SdkTracerProvider};
+use std::time::{Duration, SystemTime};
+use tracing::warn;
+
+/// Service name reported to the collector
+const SERVICE_NAME: &str = "pg-benchmark";
+
+/// Pushes benchmark metrics and spans to an OTLP collector over gRPC
+pub struct OtlpExporter {
+    meter_provider: SdkMeterProvider,
+    tracer_provider: SdkTracerProvider,
+    tracer: SdkTracer,
+    iteration_spans: bool,
+    median: Gauge<f64>,
+    mean: Gauge<f64>,
+    p95: Gauge<f64>,
+    p99: Gauge<f64>,
+    rows_returned: Gauge<u64>,
+    runs: Gauge<u64>,
+}
+
+/// Parent span covering one benchmark's iterations
+pub struct BenchmarkSpan {
+    context: Context,
+}
+
+impl OtlpExporter {
+    /// Create an exporter targeting the given collector endpoint
+    ///
+    /// # Arguments
+    /// * `endpoint` - OTLP gRPC endpoint, e.g. `http://localhost:4317`
+    /// * `iteration_spans` - Emit a span per iteration in addition to the metrics
+    ///
+    /// # Returns
+    /// * `BenchmarkResult<Self>` - Exporter or setup error
+    pub fn connect(endpoint: &str, iteration_spans: bool) -> BenchmarkResult<Self> {
+        let setup_error = |e: opentelemetry_otlp::ExporterBuildError| BenchmarkError::Setup {
+            message: format!("Failed to build OTLP exporter: {}", e),
+        };
+        let resource = Resource::builder().with_service_name(SERVICE_NAME).build();
+
+        let metric_exporter = MetricExporter::builder()
+            .with_tonic()
+            .with_endpoint(endpoint)
+            .build()
+            .map_err(setup_error)?;
+        let meter_provider = SdkMeterProvider::builder()
+            .with_periodic_exporter(metric_exporter)
+            .with_resource(resource.clone())
+            .build();
+
+        let span_exporter = SpanExporter::builder()
+            .with_tonic()
+            .with_endpoint(endpoint)
+            .build()
+            .map_err(setup_error)?;
+        let tracer_provider = SdkTracerProvider::builder()
+            .with_batch_exporter(span_exporter)
+            .with_resource(resource)
+            .build();
+
+        let meter = meter_provider.meter(SERVICE_NAME);
+        let seconds_gauge = |name: &'static str, description: &'static str| {
+            meter
+                .f64_gauge(name)
+                .with_unit("s")
+                .with_description(description)
+                .build()
+        };
+
+        Ok(Self {
+            tracer: tracer_provider.tracer(SERVICE_NAME),
+            median: seconds_gauge("pg_bench.median", "Median iteration duration"),
+            mean: seconds_gauge("pg_bench.mean", "Mean iteration duration"),
+            p95: seconds_gauge("pg_bench.p95", "95th percentile iteration duration"),
+            p99: seconds_gauge("pg_bench.p99", "99th percentile iteration duration"),
+            rows_returned: meter
+                .u64_gauge("pg_bench.rows_returned")
+                .with_description("Rows returned per iteration")
+                .build(),
+            runs: meter
+                .u64_gauge("pg_bench.runs")
+                .with_description("Successful iterations")
+                .build(),
+            meter_provider,
+            tracer_provider,
+            iteration_spans,
+        })
+    }
+
+    /// Start the parent span for a benchmark
+    pub fn start_benchmark(&self, name: &str) -> BenchmarkSpan {
+        let span = self
+            .tracer
+            .span_builder(name.to_string())
+            .with_attributes(vec![KeyValue::new("benchmark", name.to_string())])
+            .start(&self.tracer);
+
+        BenchmarkSpan {
+            context: Context::current_with_span(span),
+        }
+    }
+
+    /// Record one iteration as a child span, if iteration spans are enabled
+    ///
+    /// # Arguments
+    /// * `parent` - Span returned by [`OtlpExporter::start_benchmark`]
+    /// * `iteration` - 1-based iteration number
+    /// * `start` - Wall-clock time the iteration started
+    /// * `duration` - Measured duration of the iteration
+    /// * `rows` - Rows returned by the iteration
+    pub fn record_iteration(
+        &self,
+        parent: &BenchmarkSpan,
+        iteration: usize,
+        start: SystemTime,
+        duration: Duration,
+        rows: usize,
+    ) {
+        if !self.iteration_spans {
+            return;
+        }
+
+        let mut span = self
+            .tracer
+            .span_builder("iteration")
+            .with_start_time(start)
+            .with_attributes(vec![
+                KeyValue::new("iteration", iteration as i64),
+                KeyValue::new("rows", rows as i64),
+            ])
+            .start_with_context(&self.tracer, &parent.context);
+        span.end_with_timestamp(start + duration);
+    }
+
+    /// Record a benchmark's aggregate metrics and end its parent span
+    pub fn finish_benchmark(&self, parent: BenchmarkSpan, stats: &BenchmarkStats) {
+        let attributes = [KeyValue::new("benchmark", stats.name.clone())];
+        self.median
+            .record(stats.median().as_secs_f64(), &attributes);
+        self.mean.record(stats.mean().as_secs_f64(), &attributes);
+        self.p95
+            .record(stats.percentile(95.0).as_secs_f64(), &attributes);
+        self.p99
+            .record(stats.percentile(99.0).as_secs_f64(), &attributes);
+        self.rows_returned
+            .record(stats.rows_returned as u64, &attributes);
+        self.runs.record(stats.runs.len() as u64, &attributes);
+
+        parent.context.span().end();
+    }
+
+    /// Flush everything to the collector, logging rather than failing on errors
+    pub fn shutdown(self) {
+        if let Err(e) = self.tracer_provider.shutdown() {
+            warn!("Failed to flush OTLP spans: {}", e);
+        }
+        if let Err(e) = self.meter_provider.shutdown() {
+            warn!("Failed to flush OTLP metrics: {}", e);
+        }
+    }
+}
@@ -0,0 +1,120 @@
+//! Client-side CPU profiling
+//!
+//! Enabled with the `profile-cpu` feature. While a benchmark's iterations run, the
+//! process is sampled with `pprof` and the stacks are written per benchmark as a
+//! folded-stacks file (for `inferno`/`flamegraph.pl`) and an SVG flamegraph. This
+//! attributes client CPU, such as `raw_sql_large_in`'s hex encoding or the COPY
+//! benchmarks' buffer building.
+//!
+//! Sampling is signal based and stays active during the timed section, so it adds a
+//! small overhead to the measured durations. Don't compare profiled timings with
+//! unprofiled ones. Profiling is only supported on Linux; elsewhere it is a no-op.
+
+use crate::BenchmarkResult;
+use std::path::{Path, PathBuf};
+
+/// Sampling frequency in Hz; a prime avoids lockstep with periodic work
+pub const SAMPLE_FREQUENCY: i32 = 997;
+
+#[cfg(target_os = "linux")]
+mod imp {
+    use super::{BenchmarkResult, Path, PathBuf, SAMPLE_FREQUENCY};
+    use crate::BenchmarkError;
+    use std::fmt::Write as _;
+    use std::fs;
+
+    /// Libraries whose frames are unwound unreliably from a signal handler
+    const BLOCKLIST: &[&str] = &["libc", "libgcc", "pthread", "vdso"];
+
+    /// Make a benchmark name safe to use as a file name
+    fn file_stem(name: &str) -> String {
+        name.chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | '=') {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect()
+    }
+
+    /// Samples the process until [`CpuProfiler::finish`] is called
+    pub struct CpuProfiler {
+        guard: pprof::ProfilerGuard<'static>,
+    }
+
+    impl CpuProfiler {
+        /// Start sampling the process
+        pub fn start() -> BenchmarkResult<Self> {
+            let guard = pprof::ProfilerGuardBuilder::default()
+                .frequency(SAMPLE_FREQUENCY)
+                .blocklist(BLOCKLIST)
+                .build()
+                .map_err(|e| BenchmarkError::Setup {
+                    message: format!("Failed to start CPU profiler: {}", e),
+                })?;
+            Ok(Self { guard })
+        }
+
+        /// Stop sampling and write `<name>.folded` and `<name>.svg` into `dir`
+        ///
+        /// # Arguments
+        /// * `dir` - Directory to write the profile into
+        /// * `name` - Benchmark name, used for the file names
+        ///
+        /// # Returns
+        /// * `BenchmarkResult<Option<PathBuf>>` - Path of the SVG flamegraph
+        pub fn finish(self, dir: &Path, name: &str) -> BenchmarkResult<Option<PathBuf>> {
+            let report_error = |e: pprof::Error| BenchmarkError::BenchmarkFailed {
+                message: format!("Failed to build CPU profile: {}", e),
+            };
+            let report = self.guard.report().build().map_err(report_error)?;
+            drop(self.guard);
+
+            fs::create_dir_all(dir)?;
+            let stem = file_stem(name);
+
+            // One `thread;outermost;...;innermost count` line per unique stack
+            let mut folded = String::new();
+            for (frames, count) in &report.data {
+                folded.push_str(&frames.thread_name_or_id());
+                for frame in frames.frames.iter().rev() {
+                    for symbol in frame.iter().rev() {
+                        let _ = write!(folded, ";{}", symbol);
+                    }
+                }
+                let _ = writeln!(folded, " {}", count);
+            }
+            fs::write(dir.join(format!("{}.folded", stem)), folded)?;
+
+            let svg_path = dir.join(format!("{}.svg", stem));
+            let svg = fs::File::create(&svg_path)?;
+            report.flamegraph(svg).map_err(report_error)?;
+
+            Ok(Some(svg_path))
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod imp {
+    use super::{BenchmarkResult, Path, PathBuf};
+
+    /// No-op profiler on platforms without signal-based sampling support
+    pub struct CpuProfiler;
+
+    impl CpuProfiler {
+        /// Start sampling the process (no-op on this platform)
+        pub fn start() -> BenchmarkResult<Self> {
+            Ok(Self)
+        }
+
+        /// Stop sampling (no-op on this platform)
+        pub fn finish(self, _dir: &Path, _name: &str) -> BenchmarkResult<Option<PathBuf>> {
+            Ok(None)
+        }
+    }
+}
+
+pub use imp::CpuProfiler;
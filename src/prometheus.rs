@@ -0,0 +1,159 @@
+//! Prometheus text format for benchmark results
+//!
+//! Renders one gauge per statistic with a sample per benchmark, durations in
+//! seconds. The same rendering backs the `serve` feature's `/metrics` endpoint and
+//! `.prom` files for a node_exporter textfile collector. The collector may read the
+//! file at any moment, so files are replaced atomically.
+
+use crate::utils::write_atomically;
+use crate::{BenchmarkResult, BenchmarkStats};
+use std::fmt::Write as _;
+use std::path::Path;
+use std::time::Duration;
+
+/// Metric name, help text and accessor for one per-benchmark gauge
+type Gauge<T> = (&'static str, &'static str, fn(&BenchmarkStats) -> T);
+
+/// Per-benchmark duration gauges, reported in seconds
+const DURATION_GAUGES: [Gauge<Duration>; 4] = [
+    (
+        "pg_bench_median_seconds",
+        "Median iteration duration",
+        |s| s.median(),
+    ),
+    ("pg_bench_mean_seconds", "Mean iteration duration", |s| {
+        s.mean()
+    }),
+    (
+        "pg_bench_p95_seconds",
+        "95th percentile iteration duration",
+        |s| s.percentile(95.0),
+    ),
+    (
+        "pg_bench_p99_seconds",
+        "99th percentile iteration duration",
+        |s| s.percentile(99.0),
+    ),
+];
+
+/// Per-benchmark count gauges
+const COUNT_GAUGES: [Gauge<usize>; 2] = [
+    (
+        "pg_bench_runs",
+        "Successful iterations in the last round",
+        |s| s.runs.len(),
+    ),
+    (
+        "pg_bench_rows_returned",
+        "Rows returned per iteration",
+        |s| s.rows_returned,
+    ),
+];
+
+/// Render every per-benchmark gauge, each header once followed by one sample per benchmark
+///
+/// # Arguments
+/// * `out` - Text to append to
+/// * `results` - Statistics of every benchmark to report
+pub fn write_benchmark_gauges(out: &mut String, results: &[BenchmarkStats]) {
+    for (name, help, value) in DURATION_GAUGES {
+        write_header(out, name, help, "gauge");
+        for stats in results {
+            write_sample(out, name, &stats.name, value(stats).as_secs_f64());
+        }
+    }
+    for (name, help, value) in COUNT_GAUGES {
+        write_header(out, name, help, "gauge");
+        for stats in results {
+            write_sample(out, name, &stats.name, value(stats) as f64);
+        }
+    }
+}
+
+/// Write the gauges of all results to a textfile collector file
+///
+/// # Arguments
+/// * `path` - File to write, usually `<collector dir>/<name>.prom`; replaced if it exists
+/// * `results` - Statistics of every benchmark to report
+///
+/// # Returns
+/// * `BenchmarkResult<()>` - Success or IO error
+pub fn write_textfile(path: &Path, results: &[BenchmarkStats]) -> BenchmarkResult<()> {
+    let mut out = String::new();
+    write_benchmark_gauges(&mut out, results);
+    write_atomically(path, false, |file, _| {
+        file.write_all(out.as_bytes())?;
+        Ok(())
+    })
+}
+
+/// Write the `# HELP` and `# TYPE` lines for a metric
+pub fn write_header(out: &mut String, name: &str, help: &str, kind: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+}
+
+/// Write one sample labelled with the benchmark name
+pub fn write_sample(out: &mut String, name: &str, benchmark: &str, value: f64) {
+    let _ = writeln!(
+        out,
+        "{}{{benchmark=\"{}\"}} {}",
+        name,
+        escape_label_value(benchmark),
+        value
+    );
+}
+
+/// Escape a label value: backslash, double quote and newline must be escaped
+fn escape_label_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_stats;
+
+    #[test]
+    fn textfile_has_one_gauge_line_per_metric_per_benchmark() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("pg_bench.prom");
+        let mut results = vec![
+            test_stats("any_array", &[2_000, 2_100, 2_200]),
+            test_stats("odd \"name\"\\", &[1_000]),
+        ];
+        results[0].rows_returned = 42;
+        write_textfile(&path, &results).unwrap();
+        let text = std::fs::read_to_string(&path).unwrap();
+
+        for metric in [
+            "pg_bench_median_seconds",
+            "pg_bench_mean_seconds",
+            "pg_bench_p95_seconds",
+            "pg_bench_p99_seconds",
+            "pg_bench_rows_returned",
+        ] {
+            assert_eq!(
+                text.matches(&format!("# TYPE {} gauge\n", metric)).count(),
+                1
+            );
+            assert_eq!(text.matches(&format!("# HELP {} ", metric)).count(), 1);
+            let samples: Vec<&str> = text
+                .lines()
+                .filter(|line| line.starts_with(&format!("{}{{", metric)))
+                .collect();
+            assert_eq!(samples.len(), 2, "{}", metric);
+            for sample in samples {
+                let (series, value) = sample.rsplit_once(' ').unwrap();
+                assert!(series.ends_with("\"}"), "{}", sample);
+                value.parse::<f64>().unwrap();
+            }
+        }
+        assert!(text.contains("pg_bench_median_seconds{benchmark=\"any_array\"} 0.0021\n"));
+        assert!(text.contains("pg_bench_rows_returned{benchmark=\"any_array\"} 42\n"));
+        assert!(text.contains("{benchmark=\"odd \\\"name\\\"\\\\\"}"));
+    }
+}
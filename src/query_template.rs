@@ -0,0 +1,65 @@
+//! User-supplied query templates
+//!
+//! By default every benchmark queries the `overrides` table. A template replaces
+//! that final query so the harness can run against an arbitrary schema:
+//!
+//! ```text
+//! SELECT foo AS response FROM my_table WHERE my_key IN {ids}
+//! ```
+//!
+//! Each strategy substitutes `{ids}` with its own parenthesised ID-set expression,
+//! e.g. an inline literal list, `($1, $2, ...)`, `(SELECT UNNEST($1))` or
+//! `(SELECT id FROM temp_ids_<random>)`, so the placeholder must be used as the right-hand
+//! side of `IN`. Rows are read into [`crate::ExampleData`], so the template must
+//! return a single non-empty text column named `response`.
+
+use std::fmt;
+use std::str::FromStr;
+
+/// Placeholder replaced with each strategy's ID-set expression
+pub const IDS_PLACEHOLDER: &str = "{ids}";
+
+/// A validated query template containing the `{ids}` placeholder
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryTemplate {
+    template: String,
+}
+
+impl QueryTemplate {
+    /// Substitute the strategy's ID-set expression into the template
+    ///
+    /// # Arguments
+    /// * `ids_expr` - Parenthesised expression usable as the right-hand side of `IN`
+    ///
+    /// # Returns
+    /// * `String` - Complete SQL query
+    pub fn render(&self, ids_expr: &str) -> String {
+        self.template.replace(IDS_PLACEHOLDER, ids_expr)
+    }
+}
+
+impl FromStr for QueryTemplate {
+    type Err = String;
+
+    fn from_str(template: &str) -> Result<Self, Self::Err> {
+        if template.trim().is_empty() {
+            return Err("query template must not be empty".to_string());
+        }
+        if !template.contains(IDS_PLACEHOLDER) {
+            return Err(format!(
+                "query template must contain the `{}` placeholder, e.g. `... WHERE key IN {}`",
+                IDS_PLACEHOLDER, IDS_PLACEHOLDER
+            ));
+        }
+
+        Ok(Self {
+            template: template.to_string(),
+        })
+    }
+}
+
+impl fmt::Display for QueryTemplate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.template)
+    }
+}
@@ -0,0 +1,339 @@
+//! Picking a single strategy to use
+//!
+//! Ranks the benchmarks of one run by an objective and recommends the best one only
+//! if it beats the runner-up by a statistically meaningful margin:
+//!
+//! * `latency` - lowest median; the two medians' 95% distribution-free confidence
+//!   intervals (from order statistics) must not overlap
+//! * `throughput` - most IDs per second (input size over mean duration); the 95%
+//!   confidence intervals of the two throughputs must not overlap
+//! * `stability` - lowest coefficient of variation; the runs' relative deviations
+//!   from their median must be significantly smaller by a Mann-Whitney U test at the
+//!   5% level (a rank-based analogue of the Brown-Forsythe test)
+//!
+//! Non-overlapping intervals are a conservative criterion: a gap this clear is
+//! significant, while a small overlap may still be, and is reported as no winner.
+
+use crate::compare::mann_whitney;
+use crate::{BenchmarkStats, POWER_Z_ALPHA};
+use clap::ValueEnum;
+use std::fmt::Write as _;
+use std::time::Duration;
+
+/// Significance level of the stability test
+const ALPHA: f64 = 0.05;
+
+/// What the recommended strategy should be best at
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Objective {
+    /// Lowest median duration
+    Latency,
+    /// Most IDs looked up per second, from the mean duration
+    Throughput,
+    /// Least run-to-run variation relative to the typical duration
+    Stability,
+}
+
+/// Outcome of ranking one run's benchmarks
+#[derive(Debug, Clone)]
+pub struct Recommendation {
+    /// Best benchmark by the objective, whether or not it won significantly
+    pub best: String,
+    /// Second-best benchmark, if there was more than one candidate
+    pub runner_up: Option<String>,
+    /// Whether `best` beat `runner_up` significantly (always true without a runner-up)
+    pub significant: bool,
+    /// Human-readable ranking and decision, one line per entry
+    pub reasoning: String,
+}
+
+impl Recommendation {
+    /// Name to recommend, or `None` if there is no significant winner
+    pub fn recommended(&self) -> Option<&str> {
+        self.significant.then_some(self.best.as_str())
+    }
+}
+
+/// A candidate's score under an objective, with its confidence interval
+struct Scored<'a> {
+    stats: &'a BenchmarkStats,
+    /// Lower is better for every objective; throughput is negated
+    score: f64,
+    /// Interval of the objective's natural value, for the reasoning and overlap test
+    interval: Option<(f64, f64)>,
+}
+
+/// Rank the benchmarks by `objective` and decide whether the best one clearly wins
+///
+/// # Arguments
+/// * `results` - Results of one run; benchmarks without successful runs are ignored
+/// * `objective` - What to optimise for
+///
+/// # Returns
+/// * `Option<Recommendation>` - `None` if no benchmark produced any runs
+pub fn recommend(results: &[BenchmarkStats], objective: Objective) -> Option<Recommendation> {
+    let mut candidates: Vec<Scored> = results
+        .iter()
+        .filter(|stats| !stats.runs.is_empty())
+        .map(|stats| score(stats, objective))
+        .collect();
+    candidates.sort_by(|a, b| a.score.total_cmp(&b.score));
+
+    let best = candidates.first()?;
+    let runner_up = candidates.get(1);
+
+    let mut reasoning = String::new();
+    let _ = writeln!(reasoning, "Objective: {}", describe(objective));
+    for (rank, candidate) in candidates.iter().enumerate() {
+        let _ = writeln!(
+            reasoning,
+            "  {}. {:<35} {}",
+            rank + 1,
+            candidate.stats.name,
+            format_value(candidate, objective)
+        );
+    }
+
+    let significant = match runner_up {
+        None => {
+            let _ = writeln!(reasoning, "Only one benchmark produced results");
+            true
+        }
+        Some(runner_up) => {
+            let (significant, detail) = beats(best, runner_up, objective);
+            let _ = writeln!(
+                reasoning,
+                "{} vs {}: {}",
+                best.stats.name, runner_up.stats.name, detail
+            );
+            significant
+        }
+    };
+    if significant {
+        let _ = writeln!(reasoning, "Recommendation: {}", best.stats.name);
+    } else {
+        let _ = writeln!(
+            reasoning,
+            "No recommendation: {} is not significantly better than the runner-up",
+            best.stats.name
+        );
+    }
+
+    Some(Recommendation {
+        best: best.stats.name.clone(),
+        runner_up: runner_up.map(|r| r.stats.name.clone()),
+        significant,
+        reasoning,
+    })
+}
+
+/// Score one benchmark under the objective
+fn score(stats: &BenchmarkStats, objective: Objective) -> Scored<'_> {
+    match objective {
+        Objective::Latency => Scored {
+            stats,
+            score: stats.median().as_secs_f64(),
+            interval: median_interval(&stats.runs),
+        },
+        Objective::Throughput => {
+            let throughput = |seconds: f64| stats.input_size as f64 / seconds;
+            let mean = stats.mean().as_secs_f64();
+            let interval = (stats.runs.len() >= 2).then(|| {
+                let margin = POWER_Z_ALPHA * stats.std_deviation().as_secs_f64()
+                    / (stats.runs.len() as f64).sqrt();
+                let upper = if mean > margin {
+                    throughput(mean - margin)
+                } else {
+                    f64::INFINITY
+                };
+                (throughput(mean + margin), upper)
+            });
+            Scored {
+                stats,
+                score: -throughput(mean),
+                interval,
+            }
+        }
+        Objective::Stability => Scored {
+            stats,
+            score: stats.coefficient_of_variation(),
+            interval: None,
+        },
+    }
+}
+
+/// Whether `best` significantly beats `runner_up`, with an explanation
+fn beats(best: &Scored, runner_up: &Scored, objective: Objective) -> (bool, String) {
+    match objective {
+        Objective::Latency | Objective::Throughput => {
+            let (Some(a), Some(b)) = (best.interval, runner_up.interval) else {
+                return (false, "too few runs for a confidence interval".to_string());
+            };
+            // Latency: best's interval must lie below; throughput: above
+            let separated = match objective {
+                Objective::Latency => a.1 < b.0,
+                _ => a.0 > b.1,
+            };
+            let verdict = if separated {
+                "95% confidence intervals do not overlap"
+            } else {
+                "95% confidence intervals overlap"
+            };
+            (separated, verdict.to_string())
+        }
+        Objective::Stability => {
+            let test = mann_whitney(
+                &relative_deviations(&runner_up.stats.runs),
+                &relative_deviations(&best.stats.runs),
+            );
+            match test {
+                Some(test) if test.p_value < ALPHA && test.effect_size > 0.0 => (
+                    true,
+                    format!("relative deviations smaller (p = {:.4})", test.p_value),
+                ),
+                Some(test) => (
+                    false,
+                    format!(
+                        "relative deviations not significantly smaller (p = {:.4})",
+                        test.p_value
+                    ),
+                ),
+                None => (false, "too few runs to compare".to_string()),
+            }
+        }
+    }
+}
+
+/// Distribution-free 95% confidence interval of the median, in seconds
+///
+/// Uses the order statistics at ranks `n/2 ∓ z·√n/2` (normal approximation to the
+/// binomial). With few runs the ranks clamp to the sample's extremes.
+fn median_interval(runs: &[Duration]) -> Option<(f64, f64)> {
+    if runs.len() < 2 {
+        return None;
+    }
+    let mut sorted = runs.to_vec();
+    sorted.sort_unstable();
+    let n = sorted.len() as f64;
+    let half_width = POWER_Z_ALPHA * n.sqrt() / 2.0;
+    // 1-based ranks, converted to 0-based indices
+    let lower = ((n / 2.0 - half_width).floor().max(1.0) as usize) - 1;
+    let upper = ((n / 2.0 + half_width).ceil().min(n) as usize) - 1;
+    Some((sorted[lower].as_secs_f64(), sorted[upper].as_secs_f64()))
+}
+
+/// Absolute deviation of each run from the median, as a fraction of the median
+fn relative_deviations(runs: &[Duration]) -> Vec<f64> {
+    let mut stats = BenchmarkStats::new(String::new(), String::new(), 0);
+    stats.runs = runs.to_vec();
+    let median = stats.median().as_secs_f64();
+    if median == 0.0 {
+        return Vec::new();
+    }
+    runs.iter()
+        .map(|run| (run.as_secs_f64() - median).abs() / median)
+        .collect()
+}
+
+/// One-line description of the objective
+fn describe(objective: Objective) -> &'static str {
+    match objective {
+        Objective::Latency => "latency (lowest median)",
+        Objective::Throughput => "throughput (most IDs per second)",
+        Objective::Stability => "stability (lowest coefficient of variation)",
+    }
+}
+
+/// Format a candidate's value and interval for the reasoning
+fn format_value(candidate: &Scored, objective: Objective) -> String {
+    match objective {
+        Objective::Latency => {
+            let value = format!("median {:.2?}", candidate.stats.median());
+            match candidate.interval {
+                Some((lower, upper)) => format!(
+                    "{} (95% CI {:.2?} - {:.2?})",
+                    value,
+                    Duration::from_secs_f64(lower),
+                    Duration::from_secs_f64(upper)
+                ),
+                None => value,
+            }
+        }
+        Objective::Throughput => {
+            let value = format!("{:.0} IDs/s", -candidate.score);
+            match candidate.interval {
+                Some((lower, upper)) => {
+                    format!("{} (95% CI {:.0} - {:.0})", value, lower, upper)
+                }
+                None => value,
+            }
+        }
+        Objective::Stability => format!("CV {:.4}", candidate.score),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_stats;
+
+    /// 50 runs cycling through `base`, `base + step`, ..., `base + 9 step` microseconds
+    fn runs(name: &str, base: u64, step: u64) -> BenchmarkStats {
+        let runs: Vec<u64> = (0..50).map(|i| base + (i % 10) * step).collect();
+        test_stats(name, &runs)
+    }
+
+    #[test]
+    fn a_clear_winner_is_recommended_for_each_objective() {
+        let fast = runs("any_array", 1_000, 1);
+        let slow = runs("unnest_array", 2_000, 1);
+        for objective in [Objective::Latency, Objective::Throughput] {
+            let recommendation = recommend(&[slow.clone(), fast.clone()], objective).unwrap();
+            assert_eq!(
+                recommendation.recommended(),
+                Some("any_array"),
+                "{:?}",
+                objective
+            );
+            assert_eq!(recommendation.runner_up.as_deref(), Some("unnest_array"));
+            assert!(recommendation.reasoning.contains("do not overlap"));
+        }
+
+        // Same typical duration, but one strays far further from it
+        let steady = runs("any_array", 1_000, 1);
+        let noisy = runs("raw_sql", 1_000, 100);
+        let recommendation = recommend(&[noisy, steady], Objective::Stability).unwrap();
+        assert_eq!(recommendation.recommended(), Some("any_array"));
+        assert!(
+            recommendation
+                .reasoning
+                .contains("Recommendation: any_array")
+        );
+    }
+
+    #[test]
+    fn overlapping_runs_have_no_recommendation() {
+        let first = runs("any_array", 1_000, 10);
+        let second = runs("any_array_again", 1_001, 10);
+        for objective in [
+            Objective::Latency,
+            Objective::Throughput,
+            Objective::Stability,
+        ] {
+            let recommendation = recommend(&[first.clone(), second.clone()], objective).unwrap();
+            // A best is still named, but not recommended, so `recommend` exits with 2
+            assert!(!recommendation.significant, "{:?}", objective);
+            assert_eq!(recommendation.recommended(), None);
+            assert!(recommendation.reasoning.contains("No recommendation"));
+        }
+    }
+
+    #[test]
+    fn a_lone_benchmark_wins_and_no_runs_recommend_nothing() {
+        let lone = runs("any_array", 1_000, 1);
+        let recommendation = recommend(&[lone, test_stats("failed", &[])], Objective::Latency);
+        assert_eq!(recommendation.unwrap().recommended(), Some("any_array"));
+
+        assert!(recommend(&[test_stats("failed", &[])], Objective::Latency).is_none());
+    }
+}
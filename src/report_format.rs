@@ -0,0 +1,99 @@
+//! Reports a run can write, selected with `--output`
+
+use std::fmt;
+use std::str::FromStr;
+
+/// Reports written when `--output` isn't given: everything a run wrote before the flag
+/// existed
+pub const DEFAULT_REPORT_FORMATS: [ReportFormat; 3] =
+    [ReportFormat::Text, ReportFormat::Csv, ReportFormat::Json];
+
+/// One kind of report file
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReportFormat {
+    /// `benchmark_results.log`, the human-readable log
+    Text,
+    /// `raw_results.csv`, `summary.csv`, `server_timing.csv` and `histogram.csv`
+    Csv,
+    /// `results.json`, one array element per benchmark
+    Json,
+    /// A Markdown table and ranking, e.g. for a pull request
+    Markdown,
+    /// A standalone HTML page with a latency chart
+    Html,
+}
+
+impl ReportFormat {
+    /// Every format, in the order they are listed in errors and help
+    pub const ALL: [ReportFormat; 5] = [
+        ReportFormat::Text,
+        ReportFormat::Csv,
+        ReportFormat::Json,
+        ReportFormat::Markdown,
+        ReportFormat::Html,
+    ];
+
+    /// Name used on the command line
+    pub fn name(&self) -> &'static str {
+        match self {
+            ReportFormat::Text => "text",
+            ReportFormat::Csv => "csv",
+            ReportFormat::Json => "json",
+            ReportFormat::Markdown => "markdown",
+            ReportFormat::Html => "html",
+        }
+    }
+}
+
+impl FromStr for ReportFormat {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|format| format.name().eq_ignore_ascii_case(name.trim()))
+            .ok_or_else(|| {
+                let names: Vec<&str> = Self::ALL.iter().map(ReportFormat::name).collect();
+                format!(
+                    "unknown output format `{}`; expected one of {}",
+                    name,
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+impl fmt::Display for ReportFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_format_parses_from_its_name() {
+        for (name, format) in [
+            ("text", ReportFormat::Text),
+            ("csv", ReportFormat::Csv),
+            ("json", ReportFormat::Json),
+            ("markdown", ReportFormat::Markdown),
+            ("html", ReportFormat::Html),
+        ] {
+            assert_eq!(name.parse::<ReportFormat>(), Ok(format));
+            assert_eq!(format.to_string(), name);
+        }
+        assert_eq!("HTML".parse::<ReportFormat>(), Ok(ReportFormat::Html));
+    }
+
+    #[test]
+    fn unknown_format_lists_the_valid_ones() {
+        let error = "xml".parse::<ReportFormat>().unwrap_err();
+        assert_eq!(
+            error,
+            "unknown output format `xml`; expected one of text, csv, json, markdown, html"
+        );
+    }
+}
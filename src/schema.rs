@@ -0,0 +1,178 @@
+//! Target table and columns queried by the benchmarks
+//!
+//! By default every strategy looks IDs up in `overrides`, matching `hash` and
+//! returning `response`. A [`SchemaConfig`] points the built-in queries at another
+//! table instead, as long as it has a BYTEA key column and a text value column.
+//! The value column is aliased to `response` so rows still read into
+//! [`crate::ExampleData`].
+//!
+//! The key type benchmarks and `cte_prefilter_join` query the tables created by the
+//! setup scripts and ignore this configuration.
+
+use std::fmt;
+
+/// Table, key column and value column used to build the benchmark queries
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaConfig {
+    /// Table looked up, optionally schema-qualified
+    pub table: String,
+    /// BYTEA column matched against the IDs
+    pub id_column: String,
+    /// Text column returned for each match
+    pub value_column: String,
+}
+
+impl Default for SchemaConfig {
+    fn default() -> Self {
+        Self {
+            table: "overrides".to_string(),
+            id_column: "hash".to_string(),
+            value_column: "response".to_string(),
+        }
+    }
+}
+
+impl SchemaConfig {
+    /// Select list yielding the value column as `response`
+    fn select_list(&self) -> String {
+        if self.value_column == "response" {
+            self.value_column.clone()
+        } else {
+            format!("{} AS response", self.value_column)
+        }
+    }
+
+    /// Query returning the value of every row whose key satisfies `predicate`
+    ///
+    /// # Arguments
+    /// * `predicate` - Condition on the key column, e.g. `= ANY($1)` or `IN ($1, $2)`
+    ///
+    /// # Returns
+    /// * `String` - `SELECT <value> FROM <table> WHERE <key> <predicate>;`
+    pub fn lookup_query(&self, predicate: &str) -> String {
+        format!(
+            "SELECT {} FROM {} WHERE {} {};",
+            self.select_list(),
+            self.table,
+            self.id_column,
+            predicate
+        )
+    }
+
+    /// Query joining the table against a temp table of IDs in its `id` column
+    ///
+    /// # Arguments
+    /// * `temp_table` - Name of the loaded temp table
+    ///
+    /// # Returns
+    /// * `String` - `SELECT <value> FROM <table> JOIN <temp> ON <table>.<key> = <temp>.id;`
+    pub fn join_query(&self, temp_table: &str) -> String {
+        format!(
+            "SELECT {} FROM {} JOIN {} ON {}.{} = {}.id;",
+            self.select_list(),
+            self.table,
+            temp_table,
+            self.table,
+            self.id_column,
+            temp_table
+        )
+    }
+
+    /// Query joining the table against an inline `VALUES` list of IDs
+    ///
+    /// # Arguments
+    /// * `values` - The list, e.g. `(VALUES ($1), ($2))`
+    ///
+    /// # Returns
+    /// * `String` - `SELECT <value> FROM <table> JOIN <values> AS v(id) ON <table>.<key> = v.id;`
+    pub fn values_join_query(&self, values: &str) -> String {
+        format!(
+            "SELECT {} FROM {} JOIN {} AS v(id) ON {}.{} = v.id;",
+            self.select_list(),
+            self.table,
+            values,
+            self.table,
+            self.id_column
+        )
+    }
+
+    /// Query returning the keys themselves rather than their values
+    ///
+    /// # Arguments
+    /// * `predicate` - Condition on the key column, e.g. `= ANY($1)`
+    ///
+    /// # Returns
+    /// * `String` - `SELECT <key> FROM <table> WHERE <key> <predicate>;`
+    pub fn key_query(&self, predicate: &str) -> String {
+        format!(
+            "SELECT {} FROM {} WHERE {} {};",
+            self.id_column, self.table, self.id_column, predicate
+        )
+    }
+}
+
+impl fmt::Display for SchemaConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} ({} -> {})",
+            self.table, self.id_column, self.value_column
+        )
+    }
+}
+
+/// Parse a table or column name for interpolation into SQL
+///
+/// Accepts unquoted identifiers (letters, digits, `_` and `$`, not starting with a
+/// digit), optionally dot-separated for a schema-qualified table.
+///
+/// # Arguments
+/// * `name` - Name from the command line
+///
+/// # Returns
+/// * `Result<String, String>` - The name, or why it was rejected
+pub fn parse_identifier(name: &str) -> Result<String, String> {
+    let valid_part = |part: &str| {
+        part.chars()
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+            && part
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+    };
+    if name.split('.').all(valid_part) {
+        Ok(name.to_string())
+    } else {
+        Err(format!(
+            "`{}` is not a plain SQL identifier (letters, digits, `_`, `$`, optionally `schema.name`)",
+            name
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_matches_overrides() {
+        let schema = SchemaConfig::default();
+        assert_eq!(
+            schema.lookup_query("= ANY($1)"),
+            "SELECT response FROM overrides WHERE hash = ANY($1);"
+        );
+        assert_eq!(
+            schema.join_query("temp_ids_1"),
+            "SELECT response FROM overrides JOIN temp_ids_1 ON overrides.hash = temp_ids_1.id;"
+        );
+    }
+
+    #[test]
+    fn rejects_names_that_are_not_identifiers() {
+        assert!(parse_identifier("app.lookups").is_ok());
+        assert!(parse_identifier("value_2").is_ok());
+        assert!(parse_identifier("1table").is_err());
+        assert!(parse_identifier("t; DROP TABLE x").is_err());
+        assert!(parse_identifier("").is_err());
+    }
+}
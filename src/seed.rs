@@ -0,0 +1,153 @@
+//! Creating and filling the lookup table on a fresh database
+//!
+//! `seed` makes the suite self-contained: instead of running the setup scripts, it
+//! recreates the configured table and fills it over binary COPY. Row `n` is keyed by
+//! the same SHA-256 hash the ID generators produce for `n`, so generated IDs up to
+//! the row count find their row.
+
+use crate::copy::{BINARY_COPY_HEADER, BINARY_COPY_TRAILER, BinaryCopyWriter, CopyField};
+use crate::utils::hash_test_id;
+use crate::{BenchmarkContext, BenchmarkResult, ID_RANGE};
+use rand::{Rng, SeedableRng, rngs::StdRng};
+use tracing::info;
+
+/// Rows seeded by default: every ID the generators can draw
+pub const DEFAULT_SEED_ROWS: u64 = ID_RANGE;
+
+/// Length of each row's random response, as in `scripts/setup_test_db.sql`
+const RESPONSE_LEN: usize = 20;
+
+/// Rows between progress log lines
+const PROGRESS_ROWS: u64 = 1_000_000;
+
+/// Recreate the lookup table and fill it with `rows` rows
+///
+/// Drops any existing table first, so seeding twice leaves the same shape. The
+/// table is created and loaded in one transaction, which lets COPY write the rows
+/// already frozen; the primary key is built after the load, then the table is
+/// vacuumed and analyzed. COPY is streamed in `copy_flush_bytes` flushes.
+///
+/// # Arguments
+/// * `context` - Context whose pool and schema (table, key and value columns) are seeded
+/// * `rows` - Number of rows, keyed by the hashes of 1 to `rows`
+///
+/// # Returns
+/// * `BenchmarkResult<()>` - Success or database error
+pub async fn seed_database(context: &BenchmarkContext, rows: u64) -> BenchmarkResult<()> {
+    let schema = &context.schema;
+    info!("Seeding {} with {} rows", schema, rows);
+    let mut rng = StdRng::seed_from_u64(rand::random());
+    let mut transaction = context.pool.begin().await?;
+
+    sqlx::query(&format!("DROP TABLE IF EXISTS {};", schema.table))
+        .execute(&mut *transaction)
+        .await?;
+    sqlx::query(&format!(
+        "CREATE TABLE {} ({} BYTEA NOT NULL, {} TEXT NOT NULL);",
+        schema.table, schema.id_column, schema.value_column
+    ))
+    .execute(&mut *transaction)
+    .await?;
+
+    // FREEZE is allowed because the table was created in this transaction
+    let mut handle = transaction
+        .copy_in_raw(&format!(
+            "COPY {} ({}, {}) FROM STDIN WITH (FORMAT BINARY, FREEZE)",
+            schema.table, schema.id_column, schema.value_column
+        ))
+        .await?;
+    let tuple_size = BinaryCopyWriter::tuple_size(&[32, RESPONSE_LEN]);
+    let rows_per_flush = (context.copy_flush_bytes / tuple_size).max(1) as u64;
+    let mut buf = Vec::with_capacity(
+        BINARY_COPY_HEADER.len() + rows_per_flush as usize * tuple_size + BINARY_COPY_TRAILER.len(),
+    );
+    buf.extend_from_slice(&BINARY_COPY_HEADER);
+
+    let mut next = 1;
+    while next <= rows {
+        let end = rows.min(next + rows_per_flush - 1);
+        for id in next..=end {
+            buf.extend_from_slice(&2i16.to_be_bytes());
+            hash_test_id(id as i64).encode(&mut buf);
+            random_response(&mut rng).encode(&mut buf);
+        }
+        handle.send(buf.as_slice()).await?;
+        buf.clear();
+        if end / PROGRESS_ROWS > (next - 1) / PROGRESS_ROWS {
+            info!("Seeded {} of {} rows", end, rows);
+        }
+        next = end + 1;
+    }
+    buf.extend_from_slice(&BINARY_COPY_TRAILER);
+    handle.send(buf).await?;
+    handle.finish().await?;
+
+    // Building the index once is much faster than maintaining it row by row
+    sqlx::query(&format!(
+        "ALTER TABLE {} ADD PRIMARY KEY ({}) INCLUDE ({});",
+        schema.table, schema.id_column, schema.value_column
+    ))
+    .execute(&mut *transaction)
+    .await?;
+    transaction.commit().await?;
+
+    // VACUUM can't run inside a transaction block
+    sqlx::query(&format!("VACUUM ANALYZE {};", schema.table))
+        .execute(&context.pool)
+        .await?;
+    info!("Seeded {} with {} rows", schema.table, rows);
+    Ok(())
+}
+
+/// Random lowercase hex response of [`RESPONSE_LEN`] characters
+fn random_response(rng: &mut StdRng) -> String {
+    let bytes: [u8; RESPONSE_LEN / 2] = rng.random();
+    hex::encode(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::SchemaConfig;
+    use crate::utils::generate_test_ids;
+    use sqlx::postgres::PgPoolOptions;
+
+    /// Needs a database: `DATABASE_URL=postgres://... cargo test -- --ignored`
+    #[tokio::test]
+    #[ignore]
+    async fn seeding_twice_leaves_the_requested_rows() {
+        let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        let pool = PgPoolOptions::new().connect(&url).await.unwrap();
+        let mut context = BenchmarkContext::new(pool);
+        context.schema = SchemaConfig {
+            table: "seed_test_overrides".to_string(),
+            ..SchemaConfig::default()
+        };
+        // Several flushes per seed
+        context.copy_flush_bytes = 64 * 1024;
+
+        for _ in 0..2 {
+            seed_database(&context, 10_000).await.unwrap();
+        }
+        let rows: i64 = sqlx::query_scalar("SELECT count(*) FROM seed_test_overrides;")
+            .fetch_one(&context.pool)
+            .await
+            .unwrap();
+        assert_eq!(rows, 10_000);
+
+        // Generated IDs within the seeded range find their rows
+        let ids = generate_test_ids(100, 10_001);
+        let hits: i64 =
+            sqlx::query_scalar("SELECT count(*) FROM seed_test_overrides WHERE hash = ANY($1);")
+                .bind(&ids)
+                .fetch_one(&context.pool)
+                .await
+                .unwrap();
+        assert_eq!(hits, 100);
+
+        sqlx::query("DROP TABLE seed_test_overrides;")
+            .execute(&context.pool)
+            .await
+            .unwrap();
+    }
+}
@@ -0,0 +1,134 @@
+//! Prometheus metrics endpoint for continuous benchmarking
+//!
+//! Enabled with the `serve` feature. The suite runs periodically and publishes each
+//! successful round into a [`MetricsState`]; an HTTP server exposes the latest
+//! results on `/metrics` in the Prometheus text format. A failed round leaves the
+//! previous results in place, and `pg_bench_staleness_seconds` shows how old they are.
+
+use crate::prometheus::{write_benchmark_gauges, write_header};
+use crate::{BenchmarkResult, BenchmarkStats};
+use axum::extract::State;
+use axum::http::header;
+use axum::response::IntoResponse;
+use axum::routing::get;
+use axum::{Router, serve};
+use std::fmt::Write as _;
+use std::sync::{Arc, RwLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::net::TcpListener;
+
+/// Content type of the Prometheus text exposition format
+const PROMETHEUS_CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// Results of the most recent benchmark rounds
+#[derive(Debug, Default)]
+struct Snapshot {
+    /// Results of the last successful round
+    results: Vec<BenchmarkStats>,
+    last_success: Option<SystemTime>,
+    rounds_total: u64,
+    failed_rounds_total: u64,
+}
+
+/// Latest benchmark results, shared between the scheduler and the HTTP server
+#[derive(Debug, Default)]
+pub struct MetricsState {
+    snapshot: RwLock<Snapshot>,
+}
+
+impl MetricsState {
+    /// Create an empty state; `/metrics` only reports round counters until the first success
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Publish the results of a successful round
+    pub fn record_success(&self, results: Vec<BenchmarkStats>) {
+        let mut snapshot = self.snapshot.write().unwrap();
+        snapshot.results = results;
+        snapshot.last_success = Some(SystemTime::now());
+        snapshot.rounds_total += 1;
+    }
+
+    /// Record a failed round, keeping the last good results
+    pub fn record_failure(&self) {
+        let mut snapshot = self.snapshot.write().unwrap();
+        snapshot.rounds_total += 1;
+        snapshot.failed_rounds_total += 1;
+    }
+
+    /// Render the current state in the Prometheus text format
+    pub fn render(&self) -> String {
+        let snapshot = self.snapshot.read().unwrap();
+        let mut out = String::new();
+
+        write_benchmark_gauges(&mut out, &snapshot.results);
+
+        write_header(
+            &mut out,
+            "pg_bench_rounds_total",
+            "Benchmark rounds attempted",
+            "counter",
+        );
+        let _ = writeln!(out, "pg_bench_rounds_total {}", snapshot.rounds_total);
+        write_header(
+            &mut out,
+            "pg_bench_failed_rounds_total",
+            "Benchmark rounds that produced no results",
+            "counter",
+        );
+        let _ = writeln!(
+            out,
+            "pg_bench_failed_rounds_total {}",
+            snapshot.failed_rounds_total
+        );
+
+        if let Some(last_success) = snapshot.last_success {
+            let timestamp = last_success
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs_f64();
+            let staleness = last_success.elapsed().unwrap_or_default().as_secs_f64();
+            write_header(
+                &mut out,
+                "pg_bench_last_success_timestamp_seconds",
+                "Unix time of the last successful round",
+                "gauge",
+            );
+            let _ = writeln!(out, "pg_bench_last_success_timestamp_seconds {}", timestamp);
+            write_header(
+                &mut out,
+                "pg_bench_staleness_seconds",
+                "Seconds since the reported results were collected",
+                "gauge",
+            );
+            let _ = writeln!(out, "pg_bench_staleness_seconds {}", staleness);
+        }
+
+        out
+    }
+}
+
+/// Handler for `GET /metrics`
+async fn metrics(State(state): State<Arc<MetricsState>>) -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, PROMETHEUS_CONTENT_TYPE)],
+        state.render(),
+    )
+}
+
+/// Serve `/metrics` on the given listener until the process exits
+///
+/// # Arguments
+/// * `listener` - Bound TCP listener
+/// * `state` - Shared results to expose
+///
+/// # Returns
+/// * `BenchmarkResult<()>` - Only returns on a server IO error
+pub async fn serve_metrics(listener: TcpListener, state: Arc<MetricsState>) -> BenchmarkResult<()> {
+    let app = Router::new()
+        .route("/metrics", get(metrics))
+        .with_state(state);
+    serve(listener, app).await?;
+    Ok(())
+}
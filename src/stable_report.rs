@@ -0,0 +1,187 @@
+//! Diff-friendly text report for committing results to a repository
+//!
+//! The regular report changes on every run: it carries a timestamp, the ID seed,
+//! the client's runtime and durations down to the nanosecond. This report keeps
+//! only what describes the configuration and the performance, rounds durations to
+//! a fixed precision and orders benchmarks by name, so two runs with equivalent
+//! performance produce the same file and a diff means something moved.
+//!
+//! Included: iterations, ID-set size, batch size, statement cache, labels, planner
+//! settings, and per benchmark its name, input size, successful runs, plan node,
+//! and rounded median and 95th percentile. Excluded: timestamp, ID seed, runtime, config hash (it
+//! covers the runtime's worker count), rows returned (they depend on the random
+//! IDs), warnings, and the mean, min, max and standard deviation, which move with
+//! single outliers.
+
+use crate::{BenchmarkResult, BenchmarkStats, RunMetadata};
+use std::io::Write;
+use std::time::Duration;
+
+/// Default rounding precision for durations in the stable report
+pub const DEFAULT_STABLE_PRECISION: Duration = Duration::from_micros(100);
+
+/// Round a duration to the nearest multiple of `precision`
+///
+/// A zero precision leaves the duration unchanged.
+pub fn round_duration(duration: Duration, precision: Duration) -> Duration {
+    let step = precision.as_nanos();
+    if step == 0 {
+        return duration;
+    }
+    let rounded = (duration.as_nanos() + step / 2) / step * step;
+    Duration::from_nanos(u64::try_from(rounded).unwrap_or(u64::MAX))
+}
+
+/// Results of a run reduced to the parts that are stable across equivalent runs
+pub struct StableReport<'a> {
+    metadata: &'a RunMetadata,
+    results: Vec<&'a BenchmarkStats>,
+    precision: Duration,
+}
+
+impl<'a> StableReport<'a> {
+    /// Collect the results in a deterministic order (name, then input size)
+    ///
+    /// # Arguments
+    /// * `metadata` - Metadata of the run
+    /// * `results` - Statistics of every benchmark, in any order
+    /// * `precision` - Durations are rounded to the nearest multiple of this
+    pub fn new(
+        metadata: &'a RunMetadata,
+        results: &'a [BenchmarkStats],
+        precision: Duration,
+    ) -> Self {
+        let mut results: Vec<&BenchmarkStats> = results.iter().collect();
+        results.sort_by(|a, b| a.name.cmp(&b.name).then(a.input_size.cmp(&b.input_size)));
+        Self {
+            metadata,
+            results,
+            precision,
+        }
+    }
+
+    /// Write the report as plain text
+    ///
+    /// # Arguments
+    /// * `writer` - Destination, e.g. stdout
+    ///
+    /// # Returns
+    /// * `BenchmarkResult<()>` - Success or IO error
+    pub fn write_text<W: Write>(&self, mut writer: W) -> BenchmarkResult<()> {
+        writeln!(writer, "PostgreSQL Query Benchmark Results (stable)")?;
+        writeln!(writer, "Iterations: {}", self.metadata.iterations)?;
+        writeln!(writer, "Test IDs: {}", self.metadata.test_ids)?;
+        writeln!(writer, "Batch Size: {}", self.metadata.batch_size)?;
+        writeln!(
+            writer,
+            "Statement Cache: {}",
+            if self.metadata.statement_cache {
+                "enabled"
+            } else {
+                "disabled"
+            }
+        )?;
+        for (key, value) in &self.metadata.labels {
+            writeln!(writer, "Label: {}={}", key, value)?;
+        }
+        for (name, value) in &self.metadata.planner_settings {
+            writeln!(writer, "Planner Setting: {} = {}", name, value)?;
+        }
+        writeln!(writer, "Precision: {:?}", self.precision)?;
+        writeln!(writer)?;
+
+        writeln!(
+            writer,
+            "{:<35} {:>9} {:>8} {:>12} {:>12}  Plan",
+            "Benchmark", "InputSize", "Runs", "Median", "P95"
+        )?;
+        for result in &self.results {
+            writeln!(
+                writer,
+                "{:<35} {:>9} {:>8} {:>12} {:>12}  {}",
+                result.name,
+                result.input_size,
+                result.runs.len(),
+                format!("{:?}", round_duration(result.median(), self.precision)),
+                format!(
+                    "{:?}",
+                    round_duration(result.percentile(95.0), self.precision)
+                ),
+                result.plan_node_label()
+            )?;
+        }
+
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_stats as stats;
+    use std::collections::BTreeMap;
+
+    fn render(metadata: &RunMetadata, results: &[BenchmarkStats]) -> Vec<u8> {
+        let mut out = Vec::new();
+        StableReport::new(metadata, results, DEFAULT_STABLE_PRECISION)
+            .write_text(&mut out)
+            .unwrap();
+        out
+    }
+
+    #[test]
+    fn equivalent_runs_produce_identical_reports() {
+        let labels = BTreeMap::from([("branch".to_string(), "main".to_string())]);
+        let first_metadata = RunMetadata::new(
+            100,
+            60_000,
+            1,
+            1,
+            "current-thread".into(),
+            true,
+            labels.clone(),
+        );
+        let second_metadata = RunMetadata::new(
+            100,
+            60_000,
+            1,
+            2,
+            "multi-thread (8 workers)".into(),
+            true,
+            labels,
+        );
+        let mut first = vec![
+            stats("any_array", &[12_010, 12_020, 12_030]),
+            stats("unnest_array", &[15_000, 15_010, 15_020]),
+        ];
+        first[0].rows_returned = 3_000;
+        // Same benchmarks in another order, jittered within the precision
+        let second = vec![
+            stats("unnest_array", &[15_005, 15_012, 15_019]),
+            stats("any_array", &[12_000, 12_025, 12_031]),
+        ];
+
+        assert_eq!(
+            render(&first_metadata, &first),
+            render(&second_metadata, &second)
+        );
+    }
+
+    #[test]
+    fn rounds_to_nearest_multiple() {
+        let precision = Duration::from_micros(100);
+        assert_eq!(
+            round_duration(Duration::from_micros(1_249), precision),
+            Duration::from_micros(1_200)
+        );
+        assert_eq!(
+            round_duration(Duration::from_micros(1_250), precision),
+            Duration::from_micros(1_300)
+        );
+        assert_eq!(
+            round_duration(Duration::from_nanos(123), Duration::ZERO),
+            Duration::from_nanos(123)
+        );
+    }
+}
@@ -0,0 +1,180 @@
+//! Result correctness cross-check across strategies
+//!
+//! Every strategy answers the same question, so for the same IDs they should all
+//! return the same multiset of responses, whatever the order. Running each benchmark
+//! once against one shared ID set and comparing the sorted responses with the first
+//! benchmark's catches strategies that silently drop, duplicate or invent rows, e.g.
+//! a JOIN that multiplies rows an `IN` would return once.
+
+use crate::executor::QueryExecutor;
+use crate::{BenchmarkTest, ExampleData, IdSet};
+use std::sync::Arc;
+use tracing::{error, info};
+
+/// Outcome of the cross-check for one benchmark
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Verification {
+    pub name: String,
+    /// Rows the benchmark returned; 0 if it failed
+    pub rows: usize,
+    /// Whether the responses equal the reference benchmark's, ignoring order
+    pub matches_reference: bool,
+}
+
+/// Compare each benchmark's responses with the first successful benchmark's
+///
+/// # Arguments
+/// * `results` - Benchmark name and its rows, or `None` if it failed
+///
+/// # Returns
+/// * `Vec<Verification>` - One entry per result, in the same order; failed
+///   benchmarks never match
+pub fn compare_result_sets(results: &[(&str, Option<Vec<ExampleData>>)]) -> Vec<Verification> {
+    let sorted: Vec<Option<Vec<String>>> = results
+        .iter()
+        .map(|(_, rows)| rows.as_deref().map(sorted_responses))
+        .collect();
+    let reference = sorted.iter().flatten().next();
+
+    results
+        .iter()
+        .zip(&sorted)
+        .map(|((name, rows), responses)| Verification {
+            name: name.to_string(),
+            rows: rows.as_ref().map_or(0, Vec::len),
+            matches_reference: responses.is_some() && responses.as_ref() == reference,
+        })
+        .collect()
+}
+
+/// Run each benchmark once with the same IDs and compare their result sets
+///
+/// Benchmarks that don't return plain responses (see
+/// [`BenchmarkTest::returns_responses`]) are left out. A failed benchmark is logged
+/// and reported as not matching.
+///
+/// # Arguments
+/// * `executor` - Executor to run against
+/// * `benchmarks` - Benchmarks to check; the first comparable one is the reference
+/// * `ids` - IDs every benchmark queries, each in its own representation
+///
+/// # Returns
+/// * `Vec<Verification>` - One entry per compared benchmark, in the order given
+pub async fn verify_benchmarks(
+    executor: &dyn QueryExecutor,
+    benchmarks: &[Arc<dyn BenchmarkTest>],
+    ids: &IdSet,
+) -> Vec<Verification> {
+    let mut results = Vec::new();
+    for benchmark in benchmarks {
+        if !benchmark.returns_responses() {
+            info!(
+                "Skipping {}: its rows are not comparable responses",
+                benchmark.name()
+            );
+            continue;
+        }
+        let rows = match executor
+            .execute(benchmark.as_ref(), ids.get(benchmark.id_kind()))
+            .await
+        {
+            Ok(rows) => Some(rows),
+            Err(e) => {
+                error!("Failed to verify {}: {}", benchmark.name(), e);
+                None
+            }
+        };
+        results.push((benchmark.name(), rows));
+    }
+    compare_result_sets(&results)
+}
+
+/// Responses sorted, so result sets compare as multisets
+fn sorted_responses(results: &[ExampleData]) -> Vec<String> {
+    let mut responses: Vec<String> = results.iter().map(|row| row.response.clone()).collect();
+    responses.sort_unstable();
+    responses
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BenchmarkContext, BenchmarkIds, BenchmarkResult};
+    use async_trait::async_trait;
+
+    /// Benchmark that is only ever run through [`CannedExecutor`]
+    struct Named(&'static str);
+
+    #[async_trait]
+    impl BenchmarkTest for Named {
+        async fn run(
+            &self,
+            _context: &BenchmarkContext,
+            _ids: BenchmarkIds<'_>,
+        ) -> BenchmarkResult<Vec<ExampleData>> {
+            unreachable!("run through CannedExecutor")
+        }
+
+        fn name(&self) -> &'static str {
+            self.0
+        }
+
+        fn description(&self) -> &'static str {
+            "Returns canned rows"
+        }
+    }
+
+    /// Executor returning fixed responses, with one extra row for `duplicating_join`
+    struct CannedExecutor;
+
+    #[async_trait]
+    impl QueryExecutor for CannedExecutor {
+        async fn reset(&self) -> BenchmarkResult<()> {
+            Ok(())
+        }
+
+        async fn execute(
+            &self,
+            benchmark: &dyn BenchmarkTest,
+            _ids: BenchmarkIds<'_>,
+        ) -> BenchmarkResult<Vec<ExampleData>> {
+            let mut responses = vec!["b", "a", "c"];
+            match benchmark.name() {
+                "reordered_any" => responses.reverse(),
+                "duplicating_join" => responses.push("a"),
+                _ => {}
+            }
+            Ok(responses
+                .into_iter()
+                .map(|response| ExampleData {
+                    response: response.to_string(),
+                    hash: None,
+                })
+                .collect())
+        }
+    }
+
+    #[tokio::test]
+    async fn flags_the_benchmark_whose_rows_diverge() {
+        let benchmarks: Vec<Arc<dyn BenchmarkTest>> = vec![
+            Arc::new(Named("in_list")),
+            Arc::new(Named("reordered_any")),
+            Arc::new(Named("duplicating_join")),
+        ];
+        let ids = IdSet::new(vec![[0u8; 32]]);
+        let verifications = verify_benchmarks(&CannedExecutor, &benchmarks, &ids).await;
+
+        let summary: Vec<(&str, usize, bool)> = verifications
+            .iter()
+            .map(|v| (v.name.as_str(), v.rows, v.matches_reference))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("in_list", 3, true),
+                ("reordered_any", 3, true),
+                ("duplicating_join", 4, false),
+            ]
+        );
+    }
+}
@@ -0,0 +1,93 @@
+//! Warnings collected during a run for the final report
+//!
+//! Problems that undermine a result (failed cache resets, invalid rows, noisy or
+//! under-sampled measurements) are logged as they happen, but those lines scroll
+//! away among thousands of iteration logs. They are also recorded as [`Warning`]s
+//! and shown together at the top of the report. A problem that repeats, such as a
+//! cache reset failing on every iteration, is recorded once per benchmark with a count.
+
+use serde::Serialize;
+use std::fmt;
+
+/// What kind of problem a warning describes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WarningCategory {
+    /// Caches could not be cleared, so runs may have been warm
+    CacheClear,
+    /// Iterations or warmups failed and were left out of the results
+    Failures,
+    /// Returned rows broke a benchmark's invariants or disagreed across strategies
+    Rows,
+    /// The query plan was unexpected (e.g. a sequential scan) or could not be captured
+    Plan,
+    /// Some runs were far slower than the rest
+    Outliers,
+    /// Too few iterations for the requested minimum detectable difference
+    SampleSize,
+    /// Runs were too fast to measure per query
+    NoiseFloor,
+    /// Checkpoints or autovacuum ran during the benchmark
+    Background,
+    /// Benchmarks that were not run
+    Skipped,
+    /// Results or profiles could not be written, or cleanup failed
+    Output,
+}
+
+impl WarningCategory {
+    /// Label used in the report
+    pub fn label(&self) -> &'static str {
+        match self {
+            WarningCategory::CacheClear => "cache clear",
+            WarningCategory::Failures => "failures",
+            WarningCategory::Rows => "rows",
+            WarningCategory::Plan => "plan",
+            WarningCategory::Outliers => "outliers",
+            WarningCategory::SampleSize => "sample size",
+            WarningCategory::NoiseFloor => "noise floor",
+            WarningCategory::Background => "background",
+            WarningCategory::Skipped => "skipped",
+            WarningCategory::Output => "output",
+        }
+    }
+}
+
+/// One problem worth surfacing in the report
+#[derive(Debug, Clone, Serialize)]
+pub struct Warning {
+    pub category: WarningCategory,
+    /// Benchmark the warning concerns, if any
+    pub benchmark: Option<String>,
+    pub message: String,
+}
+
+impl Warning {
+    /// Create a warning about a benchmark
+    pub fn new(category: WarningCategory, benchmark: &str, message: impl Into<String>) -> Self {
+        Self {
+            category,
+            benchmark: Some(benchmark.to_string()),
+            message: message.into(),
+        }
+    }
+
+    /// Create a warning about the run as a whole
+    pub fn general(category: WarningCategory, message: impl Into<String>) -> Self {
+        Self {
+            category,
+            benchmark: None,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}] ", self.category.label())?;
+        if let Some(benchmark) = &self.benchmark {
+            write!(f, "{}: ", benchmark)?;
+        }
+        write!(f, "{}", self.message)
+    }
+}